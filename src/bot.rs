@@ -0,0 +1,475 @@
+use cgmath::{Point2, Point3, Vector2, Vector3, Deg, Rad, InnerSpace, Zero};
+
+use crate::{
+	build::MAX_RESOURCES,
+	camera::pitch_clamp,
+	chest::Chests,
+	collision::CollisionWorld,
+	input::Input,
+	inventory::{Item, MAX_AMMO},
+	navmesh::NavMesh,
+	pickup::Pickups,
+	player::{Player, Life, MoveMode},
+	rng::{self, Rng},
+	storm::Circle,
+};
+
+#[derive(Debug, Clone, Copy)]
+pub struct BotSkill {
+	// seconds an enemy has to be in sight before it's shot at
+	pub reaction_time: f32,
+	// the most its aim is off by, in degrees
+	pub aim_error: f32,
+	// degrees per second it can turn
+	pub turn_speed: f32,
+}
+
+impl Default for BotSkill {
+	fn default() -> Self {
+		return Self { reaction_time: 0.6, aim_error: 3.0, turn_speed: 270.0 };
+	}
+}
+
+const SIGHT_RANGE: f32 = 50.0;
+const LOOT_RANGE: f32 = 30.0;
+// closer than this it stops walking up to enemies and strafes instead
+const FIGHT_RANGE: f32 = 15.0;
+// seconds between finding a new path to the same place
+const REPATH_INTERVAL: f32 = 1.0;
+// seconds between changes to how far off its aim is
+const AIM_JITTER_INTERVAL: f32 = 0.4;
+const WAYPOINT_REACH: f32 = 0.6;
+// how far (in degrees) it can be from where it's trying to aim and still fire
+const FIRE_TOLERANCE: f32 = 3.0;
+const STRAFE_INTERVAL: f32 = 0.8;
+// seconds without getting anywhere before jumping at whatever's in the way
+const STUCK_TIME: f32 = 0.5;
+// how close the bus has to pass its landing spot to jump out
+const DROP_DISTANCE: f32 = 20.0;
+// close enough to the landing spot to stop diving and just drift down
+const LANDING_REACH: f32 = 15.0;
+// how far it wanders off before the storm gives it somewhere to be
+const IDLE_WANDER: f32 = 10.0;
+
+// what a bot gets to know about the world
+pub struct BotView<'a> {
+	pub players: &'a [Player],
+	pub pickups: &'a Pickups,
+	pub chests: &'a Chests,
+	pub collision: &'a CollisionWorld,
+	pub navmesh: &'a NavMesh,
+	// the storm's circle right now, and the one it's closing to.
+	// nothing to get away from unless the storm is active, i.e. the match is in progress.
+	pub safe: Circle,
+	pub next_safe: Circle,
+	pub storm_active: bool,
+	// how close things have to be to pick up or open
+	pub reach: f32,
+}
+
+// plays a player by filling in an Input each update, like a person pressing keys would
+pub struct Bot {
+	// index into State::players
+	pub player: usize,
+	pub input: Input,
+	pub skill: BotSkill,
+	// players only turn left and right, so looking up and down is kept here
+	pub pitch: Deg<f32>,
+	rng: Rng,
+	// waypoints still to walk through, the next one last
+	path: Vec<Point3<f32>>,
+	destination: Option<Point3<f32>>,
+	since_path: f32,
+	// the enemy being fought (an index into State::players), and how long they've been in sight
+	target: Option<(usize, f32)>,
+	aim_offset: (f32, f32),
+	since_jitter: f32,
+	strafe: f32,
+	since_strafe: f32,
+	stuck: f32,
+	// where it's dropping to from the bus
+	landing: Option<Point3<f32>>,
+	wander: Option<Point3<f32>>,
+	// semi-automatic weapons need the trigger let go between shots
+	fired: bool,
+}
+
+impl Bot {
+	pub fn new(player: usize, skill: BotSkill, seed: u64) -> Self {
+		return Self {
+			player,
+			input: Input::new(1.0, 360.0),
+			skill,
+			pitch: Deg(0.0),
+			rng: Rng::stream(seed, rng::BOT | player as u64),
+			path: Vec::new(),
+			destination: None,
+			since_path: 0.0,
+			target: None,
+			aim_offset: (0.0, 0.0),
+			since_jitter: 0.0,
+			strafe: 1.0,
+			since_strafe: 0.0,
+			stuck: 0.0,
+			landing: None,
+			wander: None,
+			fired: false,
+		};
+	}
+
+	// forgets everything from the last match
+	pub fn restart(&mut self, seed: u64) {
+		*self = Self::new(self.player, self.skill, seed);
+		return;
+	}
+
+	// where it's looking from, and which way
+	pub fn look(&self, player: &Player) -> (Point3<f32>, Vector3<f32>) {
+		let (sin_yaw, cos_yaw) = Rad::from(player.rot_x).0.sin_cos();
+		let (sin_pitch, cos_pitch) = Rad::from(self.pitch).0.sin_cos();
+		return (player.adapt_pos_for_camera(), Vector3::new(cos_pitch * cos_yaw, sin_pitch, cos_pitch * sin_yaw));
+	}
+
+	// recoil moves its actual aim (like recoil_moves_aim does for people),
+	// so it has to pull back onto the target before it can keep firing
+	pub fn kick(&mut self, player: &mut Player, kick: Vector2<Deg<f32>>) {
+		player.rot_x += kick.x;
+		self.pitch = pitch_clamp(self.pitch.0 + kick.y.0);
+		return;
+	}
+
+	// decides what to press for this update
+	pub fn think(&mut self, dt: f32, view: &BotView) {
+		self.input.release_all();
+		let player = &(view.players[self.player]);
+		if player.life == Life::Eliminated {
+			self.target = None;
+			self.landing = None;
+			return;
+		}
+
+		match player.move_mode {
+			MoveMode::InBus => {
+				let landing = match self.landing {
+					Some(landing) => landing,
+					None => self.random_point(&(view.safe), 0.6),
+				};
+				self.landing = Some(landing);
+				self.input.jump = flat(landing - player.position).magnitude() < DROP_DISTANCE;
+				return;
+			}
+			MoveMode::Skydiving | MoveMode::Gliding => {
+				// diving until it's over the landing spot, then drifting down
+				let to = flat(self.landing.unwrap_or(player.position) - player.position);
+				if to.magnitude() > LANDING_REACH {
+					self.face(player, to, dt);
+					self.steer(player, to.normalize());
+				}
+				return;
+			}
+			MoveMode::Noclip => return,
+			MoveMode::Walking => (),
+		};
+		self.landing = None;
+
+		self.update_target(dt, player, view);
+		let position = player.position;
+		let knocked = player.life != Life::Alive;
+		let fighting = self.target.map(|(index, _)| view.players[index].position);
+
+		// out of the storm first, then towards a fight, then loot, then anywhere
+		let destination = if view.storm_active && !view.next_safe.contains(position) {
+			let center = Point3::new(view.next_safe.center.x, position.y, view.next_safe.center.y);
+			let away = flat(position - center);
+			Some(if away.is_zero() { center } else { center + away.normalize() * (0.5 * view.next_safe.radius) })
+		} else if let Some(enemy) = fighting {
+			if (enemy - position).magnitude() > FIGHT_RANGE { Some(enemy) } else { None }
+		} else if knocked {
+			self.nearest_teammate(player, view)
+		} else {
+			self.loot(player, view).or_else(|| Some(self.wander(player, view)))
+		};
+
+		let direction = match destination {
+			Some(destination) => self.walk_to(player, destination, view, dt),
+			None => Vector3::zero(),
+		};
+		let direction = match fighting {
+			// sidestepping back and forth while close
+			Some(_) if destination.is_none() => {
+				self.since_strafe += dt;
+				if self.since_strafe >= STRAFE_INTERVAL {
+					self.since_strafe = 0.0;
+					self.strafe = -self.strafe;
+				}
+				player.forward_right().1 * self.strafe
+			}
+			_ => direction,
+		};
+		self.steer(player, direction);
+
+		// jumping at whatever's in the way
+		let speed = flat(player.velocity).magnitude();
+		if !direction.is_zero() && speed < 0.5 {
+			self.stuck += dt;
+			if self.stuck >= STUCK_TIME {
+				self.stuck = 0.0;
+				self.input.amount_up = 1.0;
+				self.since_path = REPATH_INTERVAL;
+			}
+		} else {
+			self.stuck = 0.0;
+		}
+
+		match self.target {
+			Some((index, seen)) if !knocked => self.engage(dt, player, &(view.players[index]), seen),
+			_ => {
+				self.fired = false;
+				self.input.sprint = !direction.is_zero() && !knocked;
+				if !direction.is_zero() {
+					self.face(player, direction, dt);
+				}
+			}
+		};
+		if knocked {
+			return;
+		}
+
+		let wanted = view.pickups.nearest(position, view.reach).is_some_and(|index| wants(player, &(view.pickups.pickups[index].item)));
+		self.input.pick_up = wanted;
+		self.input.interacting = view.chests.nearest(position, view.reach).is_some();
+		return;
+	}
+
+	// keeps shooting at whoever it was while they're still in sight,
+	// otherwise picks the closest enemy it can see
+	fn update_target(&mut self, dt: f32, player: &Player, view: &BotView) {
+		let eye = player.adapt_pos_for_camera();
+		let visible = |index: usize| {
+			let other = &(view.players[index]);
+			if index == self.player || other.team == player.team || other.life == Life::Eliminated {
+				return None;
+			}
+			let to = aim_point(other) - eye;
+			let distance = to.magnitude();
+			if distance > SIGHT_RANGE || view.collision.raycast(eye, to, distance).is_some() {
+				return None;
+			}
+			return Some(distance);
+		};
+		self.target = match self.target {
+			Some((index, seen)) if visible(index).is_some() => Some((index, seen + dt)),
+			_ => (0..view.players.len())
+				.filter_map(|index| visible(index).map(|distance| (index, distance)))
+				.min_by(|(_, a), (_, b)| a.total_cmp(b))
+				.map(|(index, _)| (index, 0.0)),
+		};
+		return;
+	}
+
+	fn engage(&mut self, dt: f32, player: &Player, enemy: &Player, seen: f32) {
+		self.since_jitter += dt;
+		if self.since_jitter >= AIM_JITTER_INTERVAL {
+			self.since_jitter = 0.0;
+			let error = self.skill.aim_error;
+			self.aim_offset = (self.rng.range(-error, error), self.rng.range(-error, error));
+		}
+		let (eye, _) = self.look(player);
+		let to = aim_point(enemy) - eye;
+		let yaw = Deg::from(Rad(to.z.atan2(to.x))).0 + self.aim_offset.0;
+		let pitch = Deg::from(Rad(to.y.atan2(flat(to).magnitude()))).0 + self.aim_offset.1;
+		let error = self.turn(player, yaw, pitch, dt);
+
+		let armed = self.equip(player);
+		let ready = armed && seen >= self.skill.reaction_time && error < FIRE_TOLERANCE;
+		self.input.firing = ready && !self.fired;
+		self.fired = self.input.firing;
+		self.input.aiming = to.magnitude() > FIGHT_RANGE;
+		return;
+	}
+
+	// selects the first hotbar weapon that shoots straight and has something to shoot.
+	// returns whether it's out already.
+	fn equip(&mut self, player: &Player) -> bool {
+		let inventory = &(player.inventory);
+		let usable = (0..inventory.slots.len()).find(|&slot| match &(inventory.slots[slot]) {
+			Some(Item::Weapon(weapon)) => {
+				let reserve = weapon.kind.ammo().map_or(0, |ammo| inventory.ammo[ammo.index()]);
+				weapon.stats().projectile.is_none() && weapon.ammo + reserve > 0
+			}
+			_ => false,
+		});
+		let Some(slot) = usable else {
+			return false;
+		};
+		if inventory.selected != Some(slot) {
+			self.input.select_slot = Some(slot);
+			return false;
+		}
+		return true;
+	}
+
+	// the closest thing worth picking up or opening
+	fn loot(&self, player: &Player, view: &BotView) -> Option<Point3<f32>> {
+		let pickup = view.pickups.pickups
+			.iter()
+			.filter(|pickup| wants(player, &(pickup.item)))
+			.map(|pickup| pickup.position)
+			.filter(|position| (position - player.position).magnitude() <= LOOT_RANGE);
+		let chest = view.chests
+			.nearest(player.position, LOOT_RANGE)
+			.and_then(|id| view.chests.get(id))
+			.map(|chest| chest.position);
+		return pickup
+			.chain(chest)
+			.min_by(|a, b| (a - player.position).magnitude2().total_cmp(&((b - player.position).magnitude2())));
+	}
+
+	fn nearest_teammate(&self, player: &Player, view: &BotView) -> Option<Point3<f32>> {
+		return view.players
+			.iter()
+			.filter(|other| other.team == player.team && other.life == Life::Alive)
+			.map(|other| other.position)
+			.min_by(|a, b| (a - player.position).magnitude2().total_cmp(&((b - player.position).magnitude2())));
+	}
+
+	// somewhere in the circle, picked again once it gets there or the circle moves away from it.
+	// before the storm is about, just somewhere nearby.
+	fn wander(&mut self, player: &Player, view: &BotView) -> Point3<f32> {
+		let area = if view.storm_active {
+			view.next_safe
+		} else {
+			Circle { center: Point2::new(player.position.x, player.position.z), radius: IDLE_WANDER }
+		};
+		match self.wander {
+			Some(point) if area.contains(point) && flat(point - player.position).magnitude() > 2.0 => return point,
+			_ => (),
+		};
+		let point = self.random_point(&(area), 0.8);
+		self.wander = Some(point);
+		return point;
+	}
+
+	fn random_point(&mut self, circle: &Circle, scale: f32) -> Point3<f32> {
+		let distance = self.rng.next_f32().sqrt() * circle.radius * scale;
+		let (sin, cos) = self.rng.range(0.0, std::f32::consts::TAU).sin_cos();
+		return Point3::new(circle.center.x + cos * distance, 0.0, circle.center.y + sin * distance);
+	}
+
+	// follows a path to `destination`, finding a new one every so often or when it changes.
+	// returns which way to go, flat and normalized, or zero once there.
+	fn walk_to(&mut self, player: &Player, destination: Point3<f32>, view: &BotView, dt: f32) -> Vector3<f32> {
+		self.since_path += dt;
+		let moved = self.destination.is_none_or(|old| (old - destination).magnitude() > 1.0);
+		if moved || self.since_path >= REPATH_INTERVAL {
+			self.since_path = 0.0;
+			self.destination = Some(destination);
+			// straight there if there's no path to it
+			self.path = match view.navmesh.find_path(player.position, destination) {
+				Some(mut path) => {
+					path.reverse();
+					path
+				}
+				None => vec![destination],
+			};
+		}
+		while self.path.len() > 1 && flat(self.path[self.path.len() - 1] - player.position).magnitude() < WAYPOINT_REACH {
+			self.path.pop();
+		}
+		let next = self.path.last().copied().unwrap_or(destination);
+		let to = flat(next - player.position);
+		if to.magnitude() < WAYPOINT_REACH {
+			return Vector3::zero();
+		}
+		return to.normalize();
+	}
+
+	// presses whichever movement keys go `direction`, relative to which way the player faces
+	fn steer(&mut self, player: &Player, direction: Vector3<f32>) {
+		let (forward, right) = player.forward_right();
+		let (ahead, across) = (direction.dot(forward), direction.dot(right));
+		self.input.amount_forward = ahead.max(0.0);
+		self.input.amount_backward = (-ahead).max(0.0);
+		self.input.amount_right = across.max(0.0);
+		self.input.amount_left = (-across).max(0.0);
+		return;
+	}
+
+	fn face(&mut self, player: &Player, direction: Vector3<f32>, dt: f32) {
+		let yaw = Deg::from(Rad(direction.z.atan2(direction.x))).0;
+		self.turn(player, yaw, 0.0, dt);
+		return;
+	}
+
+	// turns towards `yaw` and `pitch` (in degrees) as fast as it can,
+	// returning how far off it still is
+	fn turn(&mut self, player: &Player, yaw: f32, pitch: f32, dt: f32) -> f32 {
+		let max = self.skill.turn_speed * dt;
+		let yaw_error = wrap(yaw - player.rot_x.0);
+		let yaw_step = yaw_error.clamp(-max, max);
+		self.input.mouse_moved.0 = yaw_step * self.input.dots_per_deg;
+		let pitch_error = pitch - self.pitch.0;
+		let pitch_step = pitch_error.clamp(-max, max);
+		self.pitch = pitch_clamp(self.pitch.0 + pitch_step);
+		return (yaw_error - yaw_step).abs().max((pitch_error - pitch_step).abs());
+	}
+}
+
+// whether picking it up would do anything, without having to swap something out for it
+fn wants(player: &Player, item: &Item) -> bool {
+	let inventory = &(player.inventory);
+	let free_slot = inventory.slots.iter().any(|slot| slot.is_none());
+	return match item {
+		Item::Weapon(weapon) => free_slot && !inventory.slots.iter().any(|slot| matches!(slot, Some(Item::Weapon(other)) if other.kind == weapon.kind)),
+		Item::Consumable(kind, _) => free_slot || inventory.slots.iter().any(|slot| matches!(slot, Some(Item::Consumable(other, count)) if other == kind && *count < kind.max_stack())),
+		Item::Ammo(kind, _) => inventory.ammo[kind.index()] < MAX_AMMO,
+		Item::Material(material, _) => inventory.resources.get(*material) < MAX_RESOURCES,
+	};
+}
+
+// the middle of their body
+fn aim_point(player: &Player) -> Point3<f32> {
+	return player.position + Vector3::unit_y() * (0.6 * player.height());
+}
+
+fn flat(v: Vector3<f32>) -> Vector3<f32> {
+	return Vector3::new(v.x, 0.0, v.z);
+}
+
+// into -180..180 degrees
+fn wrap(degrees: f32) -> f32 {
+	return (degrees + 180.0).rem_euclid(360.0) - 180.0;
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn wrap_keeps_the_short_way_round() {
+		assert_eq!(wrap(180.0), -180.0);
+		assert_eq!(wrap(-180.0), -180.0);
+		assert_eq!(wrap(190.0), -170.0);
+		assert_eq!(wrap(-190.0), 170.0);
+		assert_eq!(wrap(370.0), 10.0);
+		assert_eq!(wrap(0.0), 0.0);
+		return;
+	}
+
+	#[test]
+	fn turns_the_short_way_at_its_turn_speed() {
+		let player = Player::new(Point3::new(0.0, 0.0, 0.0), Deg(170.0), 0);
+		let mut bot = Bot::new(0, BotSkill::default(), 0);
+		let max = BotSkill::default().turn_speed * 0.01;
+		// across the ±180 seam, so 20 degrees one way rather than 340 the other
+		let left = bot.turn(&(player), -170.0, 0.0, 0.01);
+		assert!((bot.input.mouse_moved.0 - max * bot.input.dots_per_deg).abs() < 1e-4);
+		assert!((left - (20.0 - max)).abs() < 1e-4);
+
+		// close enough gets there in one go
+		let left = bot.turn(&(player), 171.0, -1.0, 0.01);
+		assert_eq!(left, 0.0);
+		assert_eq!(bot.pitch, Deg(-1.0));
+		return;
+	}
+}
@@ -0,0 +1,666 @@
+use std::collections::{HashMap, BTreeMap, BTreeSet, VecDeque};
+
+use cgmath::{Point3, Vector3, InnerSpace};
+
+use crate::{
+	collision::{CollisionWorld, ColliderId, Triangle, Aabb},
+	obj::Vertex,
+};
+
+// size of one grid cell, pieces are as tall as they are wide
+pub const TILE: f32 = 3.0;
+// seconds between each step of a collapse spreading away from the destroyed piece
+const COLLAPSE_DELAY: f32 = 0.2;
+
+// the most vertices any single piece needs (an unedited wall or floor: 9 tiles, 2 triangles each, both sides)
+pub const MAX_PIECE_VERTICES: usize = 9 * 2 * 2 * 3;
+
+// what every piece costs, in whichever material it's made of
+pub const PIECE_COST: u32 = 10;
+// the most of each material a player can carry
+pub const MAX_RESOURCES: u32 = 999;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildMaterial {
+	Wood,
+	Brick,
+	Metal,
+}
+
+pub struct MaterialStats {
+	pub start_health: f32,
+	pub max_health: f32,
+	// seconds to go from start_health to max_health
+	pub build_time: f32,
+}
+
+impl BuildMaterial {
+	pub const ALL: [Self; 3] = [Self::Wood, Self::Brick, Self::Metal];
+
+	pub fn index(self) -> usize {
+		return self as usize;
+	}
+
+	pub fn next(self) -> Self {
+		return Self::ALL[(self.index() + 1) % Self::ALL.len()];
+	}
+
+	// stronger materials start weaker and take longer to finish
+	pub fn stats(self) -> MaterialStats {
+		return match self {
+			Self::Wood => MaterialStats { start_health: 15.0, max_health: 150.0, build_time: 2.5 },
+			Self::Brick => MaterialStats { start_health: 9.0, max_health: 300.0, build_time: 4.0 },
+			Self::Metal => MaterialStats { start_health: 5.0, max_health: 500.0, build_time: 6.0 },
+		};
+	}
+}
+
+// materials a player is carrying
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Resources {
+	amounts: [u32; 3],
+}
+
+impl Resources {
+	pub fn get(&self, material: BuildMaterial) -> u32 {
+		return self.amounts[material.index()];
+	}
+
+	pub fn add(&mut self, material: BuildMaterial, amount: u32) {
+		let have = &mut(self.amounts[material.index()]);
+		*have = (*have + amount).min(MAX_RESOURCES);
+		return;
+	}
+
+	// takes the amount only if there's enough
+	pub fn spend(&mut self, material: BuildMaterial, amount: u32) -> bool {
+		let have = &mut(self.amounts[material.index()]);
+		if *have < amount {
+			return false;
+		}
+		*have -= amount;
+		return true;
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PieceKind {
+	Wall,
+	Floor,
+	Ramp,
+	Cone,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Cell {
+	pub x: i32,
+	pub y: i32,
+	pub z: i32,
+}
+
+impl Cell {
+	pub fn containing(p: Point3<f32>) -> Self {
+		return Self {
+			x: (p.x / TILE).floor() as i32,
+			// a little slack so standing exactly on a floor counts as being above it
+			y: (p.y / TILE + 0.01).floor() as i32,
+			z: (p.z / TILE).floor() as i32,
+		};
+	}
+
+	// the corner with the smallest coordinates
+	pub fn origin(&self) -> Point3<f32> {
+		return Point3::new(self.x as f32, self.y as f32, self.z as f32) * TILE;
+	}
+
+	pub fn step(self, side: u8) -> Self {
+		return match side {
+			0 => Self { x: self.x - 1, ..self },
+			1 => Self { z: self.z - 1, ..self },
+			2 => Self { x: self.x + 1, ..self },
+			_ => Self { z: self.z + 1, ..self },
+		};
+	}
+}
+
+// the side of a cell a horizontal direction points at:
+// 0 is -x, 1 is -z, 2 is +x, 3 is +z
+fn side_of(dir: Vector3<f32>) -> u8 {
+	if dir.x.abs() > dir.z.abs() {
+		return if dir.x > 0.0 { 2 } else { 0 };
+	}
+	return if dir.z > 0.0 { 3 } else { 1 };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Placement {
+	pub kind: PieceKind,
+	pub cell: Cell,
+	// walls: which side of the cell, always 0 or 1 (see canonical).
+	// ramps: the side they rise towards. unused otherwise.
+	pub rotation: u8,
+}
+
+impl Placement {
+	// where a piece goes when a player standing at `feet` looks along `look` from `eye`
+	pub fn from_aim(kind: PieceKind, feet: Point3<f32>, eye: Point3<f32>, look: Vector3<f32>) -> Self {
+		let own = Cell::containing(feet);
+		let flat = Vector3::new(look.x, 0.0, look.z);
+		let flat = if flat.magnitude2() > 1e-6 { flat.normalize() } else { Vector3::unit_x() };
+		let side = side_of(flat);
+		let ahead = Cell::containing(feet + flat * (TILE * 0.75));
+		let placement = match kind {
+			PieceKind::Wall => Self { kind, cell: own, rotation: side },
+			PieceKind::Floor => {
+				// floors follow the pitch, so looking up puts one overhead
+				let target = eye + look.normalize() * TILE;
+				let mut cell = Cell::containing(target);
+				cell.y = (target.y / TILE).round() as i32;
+				Self { kind, cell, rotation: 0 }
+			}
+			PieceKind::Ramp => Self { kind, cell: Cell { y: own.y, ..ahead }, rotation: side },
+			PieceKind::Cone => Self { kind, cell: Cell { y: own.y + 1, ..ahead }, rotation: 0 },
+		};
+		return placement.canonical();
+	}
+
+	// a wall between two cells is the same wall from either side,
+	// so walls are always stored on the -x or -z side of a cell
+	pub fn canonical(self) -> Self {
+		if self.kind != PieceKind::Wall || self.rotation < 2 {
+			return self;
+		}
+		return Self { cell: self.cell.step(self.rotation), rotation: self.rotation - 2, ..self };
+	}
+
+	// what has to be free for this to be placed
+	fn slot(&self) -> (PieceKind, Cell, u8) {
+		let rotation = if self.kind == PieceKind::Wall { self.rotation } else { 0 };
+		return (self.kind, self.cell, rotation);
+	}
+
+	// the piece's surface, with u and v going from 0 to 1 across it
+	fn surface(&self, u: f32, v: f32) -> Point3<f32> {
+		let (x, y, z) = match self.kind {
+			PieceKind::Wall => match self.rotation {
+				0 => (0.0, v, u),
+				_ => (u, v, 0.0),
+			},
+			PieceKind::Floor => (u, 0.0, v),
+			// rising towards `rotation`
+			PieceKind::Ramp => (u, match self.rotation {
+				0 => 1.0 - u,
+				1 => 1.0 - v,
+				2 => u,
+				_ => v,
+			}, v),
+			// a pyramid, half as tall as it is wide
+			PieceKind::Cone => (u, 0.5 - (u - 0.5).abs().max((v - 0.5).abs()), v),
+		};
+		return self.cell.origin() + Vector3::new(x, y, z) * TILE;
+	}
+
+	// two triangles in (u, v) for each tile that's still there, in tile order
+	fn surface_triangles(&self, edit: EditPattern) -> Vec<[[f32; 2]; 3]> {
+		let n = self.kind.edit_grid();
+		let size = 1.0 / n as f32;
+		let mut triangles = Vec::new();
+		for tile in (0..n * n).filter(|&tile| !edit.is_removed(tile)) {
+			let (u, v) = ((tile % n) as f32 * size, (tile / n) as f32 * size);
+			let corners = [[u, v], [u + size, v], [u + size, v + size], [u, v + size]];
+			// split along the diagonal through the middle of the piece, so cone tiles stay on its faces
+			let k = (0..4).min_by(|&a, &b| {
+				let distance = |[u, v]: [f32; 2]| (u - 0.5).powi(2) + (v - 0.5).powi(2);
+				distance(corners[a]).total_cmp(&(distance(corners[b])))
+			}).unwrap();
+			let corner = |i: usize| corners[(k + i) % 4];
+			triangles.push([corner(0), corner(1), corner(2)]);
+			triangles.push([corner(0), corner(2), corner(3)]);
+		}
+		return triangles;
+	}
+
+	pub fn triangles(&self, edit: EditPattern) -> Vec<Triangle> {
+		return self.surface_triangles(edit)
+			.into_iter()
+			.map(|[a, b, c]| Triangle::new(self.surface(a[0], a[1]), self.surface(b[0], b[1]), self.surface(c[0], c[1])))
+			.collect();
+	}
+
+	// both windings, so the pieces can be seen from either side
+	pub fn vertices(&self, edit: EditPattern) -> Vec<Vertex> {
+		let vertex = |[u, v]: [f32; 2]| Vertex { position: self.surface(u, v).into(), tex_coords: [u, v] };
+		let mut vertices = Vec::new();
+		for [a, b, c] in self.surface_triangles(edit) {
+			vertices.extend_from_slice(&[vertex(a), vertex(b), vertex(c)]);
+			vertices.extend_from_slice(&[vertex(a), vertex(c), vertex(b)]);
+		}
+		return vertices;
+	}
+
+	pub fn center(&self) -> Point3<f32> {
+		return self.surface(0.5, 0.5);
+	}
+
+	// everything it could take up, however it's edited: its corners,
+	// and the middle for the top of a cone
+	pub fn aabb(&self) -> Aabb {
+		return [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]]
+			.into_iter()
+			.fold(Aabb::from_point(self.center()), |aabb, [u, v]| aabb.with_point(self.surface(u, v)));
+	}
+
+	// the outline of the piece as edges between grid corners.
+	// pieces sharing one of these hold each other up.
+	fn edges(&self) -> [Edge; 4] {
+		let corner = |u: f32, v: f32| {
+			let p = self.surface(u, v) / TILE;
+			[p.x.round() as i32, p.y.round() as i32, p.z.round() as i32]
+		};
+		let corners = [corner(0.0, 0.0), corner(1.0, 0.0), corner(1.0, 1.0), corner(0.0, 1.0)];
+		return [0, 1, 2, 3].map(|i| {
+			let (a, b) = (corners[i], corners[(i + 1) % 4]);
+			(a.min(b), a.max(b))
+		});
+	}
+
+	// touching the terrain, which for now is flat at 0
+	fn is_grounded(&self) -> bool {
+		return self.edges().iter().any(|&(a, b)| a[1] == 0 || b[1] == 0);
+	}
+
+	// which edit tile a ray hits first, counting removed tiles too
+	pub fn aimed_tile(&self, origin: Point3<f32>, dir: Vector3<f32>, max_distance: f32) -> Option<usize> {
+		return self.triangles(EditPattern::default())
+			.iter()
+			.enumerate()
+			.filter_map(|(i, triangle)| triangle.raycast(origin, dir).map(|t| (i / 2, t)))
+			.filter(|&(_, t)| t <= max_distance)
+			.min_by(|a, b| a.1.total_cmp(&(b.1)))
+			.map(|(tile, _)| tile);
+	}
+}
+
+// which tiles have been cut out of a piece, one bit per tile,
+// row by row along the piece's surface
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EditPattern {
+	removed: u16,
+}
+
+impl EditPattern {
+	fn all(kind: PieceKind) -> u16 {
+		let n = kind.edit_grid();
+		return (1 << (n * n)) - 1;
+	}
+
+	pub fn is_removed(&self, tile: usize) -> bool {
+		return self.removed & (1 << tile) != 0;
+	}
+
+	pub fn set(&mut self, tile: usize, removed: bool) {
+		if removed {
+			self.removed |= 1 << tile;
+		} else {
+			self.removed &= !(1 << tile);
+		}
+		return;
+	}
+
+	// just the tiles that are removed, e.g. to highlight them
+	pub fn inverse(self, kind: PieceKind) -> Self {
+		return Self { removed: !self.removed & Self::all(kind) };
+	}
+
+	// a quarter turn, except walls which would end up on their side, so they're mirrored instead
+	pub fn rotate(self, kind: PieceKind) -> Self {
+		let n = kind.edit_grid();
+		let mut rotated = Self::default();
+		for row in 0..n {
+			for col in 0..n {
+				let from = match kind {
+					PieceKind::Wall => row * n + (n - 1 - col),
+					_ => (n - 1 - col) * n + row,
+				};
+				rotated.set(row * n + col, self.is_removed(from));
+			}
+		}
+		return rotated;
+	}
+
+	// a piece has to keep at least one tile
+	pub fn is_valid(&self, kind: PieceKind) -> bool {
+		return self.removed & Self::all(kind) != Self::all(kind);
+	}
+}
+
+impl PieceKind {
+	// tiles along each side of the edit grid
+	pub fn edit_grid(self) -> usize {
+		return match self {
+			Self::Wall | Self::Floor => 3,
+			Self::Ramp | Self::Cone => 2,
+		};
+	}
+}
+
+// a player's edit in progress, only applied to the piece once confirmed
+#[derive(Debug, Clone, Copy)]
+pub struct EditSession {
+	pub piece: PieceId,
+	pub pattern: EditPattern,
+	// while fire is held, whether tiles being swept over get removed or restored
+	paint: Option<bool>,
+}
+
+impl EditSession {
+	pub fn new(piece: PieceId, pattern: EditPattern) -> Self {
+		return Self { piece, pattern, paint: None };
+	}
+
+	// the first tile pressed on decides what dragging over the others does
+	pub fn update(&mut self, kind: PieceKind, aimed: Option<usize>, firing: bool) {
+		if !firing {
+			self.paint = None;
+			return;
+		}
+		let Some(tile) = aimed else {
+			return;
+		};
+		let remove = *self.paint.get_or_insert(!self.pattern.is_removed(tile));
+		let mut pattern = self.pattern;
+		pattern.set(tile, remove);
+		if pattern.is_valid(kind) {
+			self.pattern = pattern;
+		}
+		return;
+	}
+}
+
+pub type PieceId = usize;
+type Edge = ([i32; 3], [i32; 3]);
+
+#[derive(Debug, Clone, Copy)]
+pub struct Piece {
+	pub placement: Placement,
+	pub edit: EditPattern,
+	pub collider: ColliderId,
+	pub material: BuildMaterial,
+	pub health: f32,
+	// seconds since it was placed, health grows until this reaches the build time
+	pub age: f32,
+}
+
+// every placed piece, indexed by grid slot
+pub struct Buildings {
+	pieces: Vec<Option<Piece>>,
+	slots: HashMap<(PieceKind, Cell, u8), PieceId>,
+	// the support graph: which pieces share each edge.
+	// ordered so collapses happen the same way every time.
+	edges: BTreeMap<Edge, BTreeSet<PieceId>>,
+	// pieces that lost their support, and how long until they fall
+	collapsing: BTreeMap<PieceId, f32>,
+	// bumped on every change, so whoever draws the pieces knows to rebuild
+	generation: u64,
+}
+
+impl Buildings {
+	pub fn new() -> Self {
+		return Self {
+			pieces: Vec::new(),
+			slots: HashMap::new(),
+			edges: BTreeMap::new(),
+			collapsing: BTreeMap::new(),
+			generation: 0,
+		};
+	}
+
+	pub fn generation(&self) -> u64 {
+		return self.generation;
+	}
+
+	pub fn get(&self, id: PieceId) -> Option<&Piece> {
+		return self.pieces.get(id).and_then(|piece| piece.as_ref());
+	}
+
+	pub fn with_collider(&self, collider: ColliderId) -> Option<PieceId> {
+		return self.pieces().find(|(_, piece)| piece.collider == collider).map(|(id, _)| id);
+	}
+
+	pub fn pieces(&self) -> impl Iterator<Item = (PieceId, &Piece)> {
+		return self.pieces
+			.iter()
+			.enumerate()
+			.filter_map(|(id, piece)| piece.as_ref().map(|piece| (id, piece)));
+	}
+
+	// nothing below the ground, only one piece of each kind per slot,
+	// and it has to be held up by the ground or a piece that isn't falling
+	pub fn can_place(&self, placement: &Placement) -> bool {
+		if placement.cell.y < 0 || self.slots.contains_key(&(placement.slot())) {
+			return false;
+		}
+		return placement.is_grounded() || placement.edges().iter().any(|edge| {
+			self.edges.get(edge).is_some_and(|ids| ids.iter().any(|id| !self.collapsing.contains_key(id)))
+		});
+	}
+
+	// other pieces sharing an edge with this placement
+	fn neighbours(&self, placement: &Placement) -> BTreeSet<PieceId> {
+		return placement.edges()
+			.iter()
+			.filter_map(|edge| self.edges.get(edge))
+			.flatten()
+			.copied()
+			.collect();
+	}
+
+	pub fn place(&mut self, placement: Placement, material: BuildMaterial, collision: &mut CollisionWorld) -> Result<PieceId, &'static str> {
+		let placement = placement.canonical();
+		if !self.can_place(&(placement)) {
+			return Err("slot is taken");
+		}
+		let edit = EditPattern::default();
+		let collider = collision.add_dynamic(placement.triangles(edit));
+		let id = self.pieces.len();
+		let health = material.stats().start_health;
+		self.pieces.push(Some(Piece { placement, edit, collider, material, health, age: 0.0 }));
+		self.slots.insert(placement.slot(), id);
+		for edge in placement.edges() {
+			self.edges.entry(edge).or_default().insert(id);
+		}
+		self.generation += 1;
+		return Ok(id);
+	}
+
+	// rebuilds the piece's mesh and collision from the pattern
+	pub fn edit(&mut self, id: PieceId, edit: EditPattern, collision: &mut CollisionWorld) -> Result<(), &'static str> {
+		let piece = self.pieces.get_mut(id).and_then(|piece| piece.as_mut()).ok_or("no such piece")?;
+		if !edit.is_valid(piece.placement.kind) {
+			return Err("a piece needs at least one tile");
+		}
+		piece.edit = edit;
+		collision.update_dynamic(piece.collider, piece.placement.triangles(edit));
+		self.generation += 1;
+		return Ok(());
+	}
+
+	fn remove(&mut self, id: PieceId, collision: &mut CollisionWorld) -> Option<Piece> {
+		let piece = self.pieces.get_mut(id)?.take()?;
+		collision.remove_dynamic(piece.collider);
+		self.slots.remove(&(piece.placement.slot()));
+		for edge in piece.placement.edges() {
+			if let Some(ids) = self.edges.get_mut(&(edge)) {
+				ids.remove(&(id));
+				if ids.is_empty() {
+					self.edges.remove(&(edge));
+				}
+			}
+		}
+		self.collapsing.remove(&(id));
+		self.generation += 1;
+		return Some(piece);
+	}
+
+	// everything, straight away and without collapsing
+	pub fn clear(&mut self, collision: &mut CollisionWorld) {
+		for id in 0..self.pieces.len() {
+			self.remove(id, collision);
+		}
+		self.pieces.clear();
+		self.generation += 1;
+		return;
+	}
+
+	// removes a piece straight away, then schedules everything that
+	// was only held up through it to collapse
+	pub fn destroy(&mut self, id: PieceId, collision: &mut CollisionWorld) -> Option<Piece> {
+		let piece = self.remove(id, collision)?;
+
+		let mut seen = BTreeSet::new();
+		for start in self.neighbours(&(piece.placement)) {
+			if seen.contains(&(start)) || self.collapsing.contains_key(&(start)) {
+				continue;
+			}
+
+			// breadth first, so the collapse spreads outwards from the hole
+			let mut component = Vec::new();
+			let mut grounded = false;
+			let mut queue = VecDeque::from([(start, 1)]);
+			seen.insert(start);
+			while let Some((id, depth)) = queue.pop_front() {
+				let placement = self.pieces[id].unwrap().placement;
+				grounded |= placement.is_grounded();
+				component.push((id, depth));
+				for next in self.neighbours(&(placement)) {
+					if !self.collapsing.contains_key(&(next)) && seen.insert(next) {
+						queue.push_back((next, depth + 1));
+					}
+				}
+			}
+
+			if !grounded {
+				for (id, depth) in component {
+					self.collapsing.insert(id, COLLAPSE_DELAY * depth as f32);
+				}
+			}
+		}
+
+		return Some(piece);
+	}
+
+	// returns the piece if this destroyed it
+	pub fn damage(&mut self, id: PieceId, amount: f32, collision: &mut CollisionWorld) -> Option<Piece> {
+		let piece = self.pieces.get_mut(id)?.as_mut()?;
+		piece.health -= amount;
+		if piece.health > 0.0 {
+			return None;
+		}
+		return self.destroy(id, collision);
+	}
+
+	// builds up health on new pieces, and counts down collapsing ones, returning the ones that fell
+	pub fn update(&mut self, dt: f32, collision: &mut CollisionWorld) -> Vec<Piece> {
+		for piece in self.pieces.iter_mut().flatten() {
+			let stats = piece.material.stats();
+			if piece.age < stats.build_time {
+				// damage taken while building stays taken
+				let step = dt.min(stats.build_time - piece.age);
+				piece.health += (stats.max_health - stats.start_health) / stats.build_time * step;
+				piece.age += step;
+			}
+		}
+
+		let mut fallen = Vec::new();
+		for delay in self.collapsing.values_mut() {
+			*delay -= dt;
+		}
+		let ready = self.collapsing
+			.iter()
+			.filter(|&(_, &delay)| delay <= 0.0)
+			.map(|(&id, _)| id)
+			.collect::<Vec<_>>();
+		for id in ready {
+			fallen.extend(self.remove(id, collision));
+		}
+		return fallen;
+	}
+
+	// grouped by material
+	pub fn vertices(&self) -> [Vec<Vertex>; 3] {
+		let mut vertices = [Vec::new(), Vec::new(), Vec::new()];
+		for (_, piece) in self.pieces() {
+			vertices[piece.material.index()].extend(piece.placement.vertices(piece.edit));
+		}
+		return vertices;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn wall(x: i32, z: i32) -> Placement {
+		return Placement { kind: PieceKind::Wall, cell: Cell { x, y: 0, z }, rotation: 0 };
+	}
+
+	// a floor a storey up, resting on wall(x, z) and wall(x + 1, z)
+	fn floor(x: i32, z: i32) -> Placement {
+		return Placement { kind: PieceKind::Floor, cell: Cell { x, y: 1, z }, rotation: 0 };
+	}
+
+	fn build(placements: &[Placement]) -> (Buildings, CollisionWorld, Vec<PieceId>) {
+		let mut buildings = Buildings::new();
+		let mut collision = CollisionWorld::new(Vec::new());
+		let ids = placements
+			.iter()
+			.map(|&placement| buildings.place(placement, BuildMaterial::Wood, &mut(collision)).unwrap())
+			.collect();
+		return (buildings, collision, ids);
+	}
+
+	#[test]
+	fn floor_on_a_destroyed_wall_falls_after_the_delay() {
+		let (mut buildings, mut collision, ids) = build(&[wall(0, 0), floor(0, 0)]);
+		buildings.destroy(ids[0], &mut(collision)).unwrap();
+		assert_eq!(buildings.collapsing.get(&(ids[1])), Some(&(COLLAPSE_DELAY)));
+
+		assert!(buildings.update(COLLAPSE_DELAY * 0.5, &mut(collision)).is_empty());
+		assert!(buildings.get(ids[1]).is_some());
+		let fallen = buildings.update(COLLAPSE_DELAY * 0.5, &mut(collision));
+		assert_eq!(fallen.iter().map(|piece| piece.placement).collect::<Vec<_>>(), vec![floor(0, 0)]);
+		assert!(buildings.get(ids[1]).is_none());
+		return;
+	}
+
+	#[test]
+	fn structure_with_another_way_down_stays() {
+		let (mut buildings, mut collision, ids) = build(&[wall(0, 0), wall(1, 0), floor(0, 0)]);
+		buildings.destroy(ids[0], &mut(collision)).unwrap();
+		assert!(buildings.collapsing.is_empty());
+		assert!(buildings.update(10.0, &mut(collision)).is_empty());
+		assert!(buildings.get(ids[1]).is_some());
+		assert!(buildings.get(ids[2]).is_some());
+		return;
+	}
+
+	#[test]
+	fn collapse_spreads_outwards_from_the_hole() {
+		let (mut buildings, mut collision, ids) = build(&[wall(0, 0), floor(0, 0), floor(1, 0), floor(2, 0)]);
+		buildings.destroy(ids[0], &mut(collision)).unwrap();
+		for (depth, &id) in ids[1..].iter().enumerate() {
+			assert_eq!(buildings.collapsing.get(&(id)), Some(&(COLLAPSE_DELAY * (depth + 1) as f32)));
+		}
+
+		// a little over the delay each time, so rounding can't hold anything up
+		let step = COLLAPSE_DELAY * 1.001;
+		for x in 0..3 {
+			let fallen = buildings.update(step, &mut(collision));
+			// exactly what fell, and nothing else
+			assert_eq!(fallen.iter().map(|piece| piece.placement).collect::<Vec<_>>(), vec![floor(x, 0)]);
+		}
+		assert_eq!(buildings.pieces().count(), 0);
+		assert!(buildings.update(step, &mut(collision)).is_empty());
+		return;
+	}
+}
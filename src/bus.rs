@@ -0,0 +1,94 @@
+use cgmath::{Point3, Vector3, InnerSpace, EuclideanSpace};
+
+use crate::{
+	collision::cuboid,
+	obj::Vertex,
+	rng::{self, Rng},
+	storm::Circle,
+};
+
+// above the ground, which is where everyone drops from
+const HEIGHT: f32 = 120.0;
+const SPEED: f32 = 15.0;
+// width, height, length
+const SIZE: Vector3<f32> = Vector3::new(3.0, 2.5, 7.0);
+
+// flies in a straight line across the map, carrying everyone who hasn't jumped out yet
+pub struct Bus {
+	pub start: Point3<f32>,
+	pub end: Point3<f32>,
+	// along the path, 0..1
+	pub progress: f32,
+	// bumped whenever it moves, see Buildings
+	generation: u64,
+}
+
+impl Bus {
+	// a random line across `area`, never too close to its edge
+	pub fn new(area: &Circle, seed: u64) -> Self {
+		let mut rng = Rng::stream(seed, rng::BUS);
+		let (sin, cos) = rng.range(0.0, std::f32::consts::TAU).sin_cos();
+		let direction = Vector3::new(cos, 0.0, sin);
+		let side = direction.cross(Vector3::unit_y()) * rng.range(-0.3, 0.3);
+		let middle = Point3::new(area.center.x, HEIGHT, area.center.y) + side * area.radius;
+		let half = direction * (0.7 * area.radius);
+		return Self { start: middle - half, end: middle + half, progress: 0.0, generation: 0 };
+	}
+
+	// back to the start of a new line, for the next match.
+	// out of sight until it moves again, like it was before it first left.
+	pub fn restart(&mut self, area: &Circle, seed: u64) {
+		let generation = self.generation;
+		*self = Self::new(area, seed);
+		self.generation = generation;
+		return;
+	}
+
+	pub fn generation(&self) -> u64 {
+		return self.generation;
+	}
+
+	pub fn finished(&self) -> bool {
+		return self.progress >= 1.0;
+	}
+
+	pub fn direction(&self) -> Vector3<f32> {
+		return (self.end - self.start).normalize();
+	}
+
+	pub fn velocity(&self) -> Vector3<f32> {
+		return self.direction() * SPEED;
+	}
+
+	// centre of its floor
+	pub fn position(&self) -> Point3<f32> {
+		return self.start + (self.end - self.start) * self.progress.min(1.0);
+	}
+
+	pub fn update(&mut self, dt: f32) {
+		if self.finished() {
+			return;
+		}
+		self.progress = (self.progress + SPEED * dt / (self.end - self.start).magnitude()).min(1.0);
+		self.generation += 1;
+		return;
+	}
+
+	// a box pointing the way it's going, gone once it's reached the end
+	pub fn vertices(&self) -> [Vec<Vertex>; 1] {
+		let mut vertices = Vec::new();
+		if self.finished() {
+			return [vertices];
+		}
+		let forward = self.direction();
+		let right = Vector3::unit_y().cross(forward);
+		let position = self.position();
+		for triangle in cuboid(Point3::origin(), SIZE) {
+			for p in [triangle.a, triangle.b, triangle.c] {
+				let p = position + right * p.x + Vector3::unit_y() * p.y + forward * p.z;
+				vertices.push(Vertex { position: p.into(), tex_coords: [0.0, 0.0] });
+			}
+		}
+		return [vertices];
+	}
+}
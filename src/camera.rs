@@ -1,34 +1,105 @@
 use cgmath::{Vector3, Point3, InnerSpace, Deg, Rad, Matrix4, Vector2};
-use crate::input::Input;
+use crate::{input::Input, settings::Settings};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepthMode {
+	// near = 0, far = 1 with a finite far plane
+	Standard,
+	// near = 1, far = 0 with an infinite far plane.
+	// floats are much more precise near 0, which cancels out the
+	// perspective divide squashing distant depths together.
+	ReverseZ,
+}
+impl DepthMode {
+	pub fn compare(self) -> wgpu::CompareFunction {
+		return match self {
+			Self::Standard => wgpu::CompareFunction::Less,
+			Self::ReverseZ => wgpu::CompareFunction::Greater,
+		};
+	}
+	// for drawing exactly on top of what's already there
+	pub fn compare_or_equal(self) -> wgpu::CompareFunction {
+		return match self {
+			Self::Standard => wgpu::CompareFunction::LessEqual,
+			Self::ReverseZ => wgpu::CompareFunction::GreaterEqual,
+		};
+	}
+	pub fn clear_value(self) -> f32 {
+		return match self {
+			Self::Standard => 1.0,
+			Self::ReverseZ => 0.0,
+		};
+	}
+}
+
+// which axis stays fixed when the aspect ratio changes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Fov {
+	Vertical(Deg<f32>),
+	Horizontal(Deg<f32>),
+}
+impl Fov {
+	pub fn vertical(self, aspect: f32) -> Deg<f32> {
+		return match self {
+			Self::Vertical(fovy) => fovy,
+			Self::Horizontal(fovx) => Rad(2.0 * ((Rad::from(fovx).0 / 2.0).tan() / aspect).atan()).into(),
+		};
+	}
+	pub fn horizontal(self, aspect: f32) -> Deg<f32> {
+		return match self {
+			Self::Vertical(fovy) => Rad(2.0 * ((Rad::from(fovy).0 / 2.0).tan() * aspect).atan()).into(),
+			Self::Horizontal(fovx) => fovx,
+		};
+	}
+}
+
+#[derive(Debug, Clone)]
 pub struct Camera {
 	pub position: Option<Point3<f32>>,
 
 	pub rot: Vector2<Deg<f32>>,
+	// render-only offsets on top of position/rot, see CameraEffects
+	pub rot_offset: Vector2<Deg<f32>>,
+	pub pos_offset: Vector3<f32>,
 
 	aspect: f32,
-	fovy: Deg<f32>,
+	fov: Fov,
 	znear: f32,
 	zfar: f32,
+	depth_mode: DepthMode,
+
+	// aim down sights progress, 0 = hip, 1 = fully zoomed
+	aim: f32,
+	ads_zoom: f32,
+	ads_time: f32,
+	sens_match: f32,
 }
 
-fn pitch_clamp(pitch: f32) -> Deg<f32> {
+pub fn pitch_clamp(pitch: f32) -> Deg<f32> {
 	const PITCH_LIM: f32 = 90.0 - 0.0001;
 	return Deg(pitch.clamp(-PITCH_LIM, PITCH_LIM));
 }
 impl Camera {
 	pub fn new(
-		dimensions: winit::dpi::PhysicalSize<u32>
+		dimensions: winit::dpi::PhysicalSize<u32>,
+		settings: &Settings,
 	) -> Self {
 		return Self {
 			position: None,
 			rot: (Deg(90.0 /* 90deg because position updates use player's forward_right, where player's rot_x is 90deg */), Deg(0.0)).into(),
+			rot_offset: (Deg(0.0), Deg(0.0)).into(),
+			pos_offset: Vector3::new(0.0, 0.0, 0.0),
 
 			aspect: dimensions.width as f32 / dimensions.height as f32,
-			fovy: Deg(40.0),
+			fov: settings.fov,
 			znear: 0.1,
-			zfar: 100.0,
+			zfar: 100.0, // unused with DepthMode::ReverseZ
+			depth_mode: settings.depth_mode,
+
+			aim: 0.0,
+			ads_zoom: settings.ads_zoom,
+			ads_time: settings.ads_time,
+			sens_match: settings.sens_match,
 		};
 	}
 
@@ -37,6 +108,51 @@ impl Camera {
 		return;
 	}
 
+	pub fn zoom(&self) -> f32 {
+		// smoothstep so zooming eases in and out
+		let t = self.aim * self.aim * (3.0 - 2.0 * self.aim);
+		return 1.0 + (self.ads_zoom - 1.0) * t;
+	}
+
+	// the vertical fov actually rendered with, including zoom
+	pub fn fovy(&self) -> Deg<f32> {
+		let hip = Rad::from(self.fov.vertical(self.aspect));
+		return Rad(2.0 * ((hip.0 / 2.0).tan() / self.zoom()).atan()).into();
+	}
+
+	// how much mouse movement should be scaled by at the current zoom.
+	// monitor distance matching: a flick to the point sens_match of the way
+	// from the centre to the edge of the screen (horizontally) lands on the
+	// same point at any zoom. 0 is the limit where the centre of the screen matches.
+	pub fn sensitivity_scale(&self) -> f32 {
+		let hip = Rad::from(self.fov.horizontal(self.aspect)).0 / 2.0;
+		let zoomed = Rad::from(Fov::Vertical(self.fovy()).horizontal(self.aspect)).0 / 2.0;
+		if self.sens_match <= 0.0 {
+			return zoomed.tan() / hip.tan();
+		}
+		return (self.sens_match * zoomed.tan()).atan() / (self.sens_match * hip.tan()).atan();
+	}
+
+	pub fn update_zoom(&mut self, input: &Input, dt: f32) {
+		let target = if input.aiming { 1.0 } else { 0.0 };
+		let step = if self.ads_time > 0.0 { dt / self.ads_time } else { 1.0 };
+		self.aim += (target - self.aim).clamp(-step, step);
+		return;
+	}
+
+	pub fn clear_offsets(&mut self) {
+		self.rot_offset = (Deg(0.0), Deg(0.0)).into();
+		self.pos_offset = Vector3::new(0.0, 0.0, 0.0);
+		return;
+	}
+
+	// where the camera is aimed, without any effects on top
+	pub fn look_dir(&self) -> Vector3<f32> {
+		let (sin_yaw, cos_yaw) = Rad::from(self.rot.x).0.sin_cos();
+		let (sin_pitch, cos_pitch) = Rad::from(self.rot.y).0.sin_cos();
+		return Vector3::new(cos_pitch * cos_yaw, sin_pitch, cos_pitch * sin_yaw).normalize();
+	}
+
 	pub fn set_pos(&mut self, pos: Point3<f32>) {
 		self.position = Some(pos);
 		return;
@@ -79,21 +195,40 @@ impl<'a> CameraUniform {
 		};
 	}
 	pub fn set_view_projection_matrix(&self, queue: &wgpu::Queue, camera: &Camera) {
-		let (sin_yaw, cos_yaw) = Rad::from(camera.rot.x).0.sin_cos();
-		let (sin_pitch, cos_pitch) = Rad::from(camera.rot.y).0.sin_cos();
+		let yaw = camera.rot.x + camera.rot_offset.x;
+		let pitch = pitch_clamp(camera.rot.y.0 + camera.rot_offset.y.0);
+		let (sin_yaw, cos_yaw) = Rad::from(yaw).0.sin_cos();
+		let (sin_pitch, cos_pitch) = Rad::from(pitch).0.sin_cos();
 		let target = Vector3::new(
 			cos_pitch * cos_yaw,
 			sin_pitch,
 			cos_pitch * sin_yaw
 		).normalize();
 
-        let view = Matrix4::look_to_rh(camera.position.unwrap(), target, Vector3::unit_y());
-		let proj = cgmath::perspective(camera.fovy, camera.aspect, camera.znear, camera.zfar);
-		let transformed_proj: [[f32; 4]; 4] = (Self::OPENGL_TO_WGPU_MATRIX * proj * view).into();
+        let view = Matrix4::look_to_rh(camera.position.unwrap() + camera.pos_offset, target, Vector3::unit_y());
+		let proj = match camera.depth_mode {
+			DepthMode::Standard => {
+				Self::OPENGL_TO_WGPU_MATRIX * cgmath::perspective(camera.fovy(), camera.aspect, camera.znear, camera.zfar)
+			}
+			DepthMode::ReverseZ => Self::reverse_z_perspective(camera.fovy(), camera.aspect, camera.znear),
+		};
+		let transformed_proj: [[f32; 4]; 4] = (proj * view).into();
 		queue.write_buffer(&(self.buffer), 0, bytemuck::cast_slice(&(transformed_proj)));
 
 		return;
 	}
+	// already in wgpu's 0..1 clip space, so no OPENGL_TO_WGPU_MATRIX.
+	// depth = znear / distance: 1 at the near plane, approaching 0 at infinity.
+	fn reverse_z_perspective(fovy: Deg<f32>, aspect: f32, znear: f32) -> Matrix4<f32> {
+		let f = 1.0 / (Rad::from(fovy).0 / 2.0).tan();
+		return Matrix4::new(
+			f / aspect, 0.0, 0.0, 0.0,
+			0.0, f, 0.0, 0.0,
+			0.0, 0.0, 0.0, -1.0,
+			0.0, 0.0, znear, 0.0,
+		);
+	}
+
 	pub fn as_entire_binding(&self) -> wgpu::BindingResource {
 		return self.buffer.as_entire_binding();
 	}
@@ -0,0 +1,126 @@
+use cgmath::{Deg, Vector2, Vector3};
+use crate::{camera::{Camera, pitch_clamp}, player::Player, settings::Settings};
+
+// procedural camera motion layered on top of the aim.
+// everything here ends up in Camera::rot_offset/pos_offset, which only
+// affect rendering, so the player's actual aim is left alone
+// (unless recoil_moves_aim is set).
+#[derive(Debug, Clone)]
+pub struct CameraEffects {
+	// 0..1, shake strength is trauma squared so small hits are subtle
+	pub trauma: f32,
+	pub trauma_decay: f32,
+	pub shake_frequency: f32,
+	pub max_shake_rot: Vector2<Deg<f32>>,
+	pub max_shake_offset: f32,
+
+	// how quickly recoil returns to the original aim (1/s)
+	pub recoil_recovery: f32,
+	pub recoil_moves_aim: bool,
+	recoil: Vector2<Deg<f32>>,
+
+	// spring for the dip when landing
+	pub landing_stiffness: f32,
+	pub landing_damping: f32,
+	landing_offset: f32,
+	landing_velocity: f32,
+
+	time: f32,
+}
+
+// smooth value noise in -1..1
+fn noise(seed: u32, t: f32) -> f32 {
+	fn hash(seed: u32, i: i32) -> f32 {
+		let mut x = (i as u32).wrapping_mul(0x9e3779b9) ^ seed.wrapping_mul(0x85ebca6b);
+		x ^= x >> 16;
+		x = x.wrapping_mul(0x7feb352d);
+		x ^= x >> 15;
+		x = x.wrapping_mul(0x846ca68b);
+		x ^= x >> 16;
+		return (x as f32 / u32::MAX as f32) * 2.0 - 1.0;
+	}
+	let i = t.floor();
+	let f = t - i;
+	let f = f * f * (3.0 - 2.0 * f);
+	let a = hash(seed, i as i32);
+	let b = hash(seed, i as i32 + 1);
+	return a + (b - a) * f;
+}
+
+impl CameraEffects {
+	pub fn new(settings: &Settings) -> Self {
+		return Self {
+			trauma: 0.0,
+			trauma_decay: 1.0,
+			shake_frequency: 15.0,
+			max_shake_rot: (Deg(3.0), Deg(3.0)).into(),
+			max_shake_offset: 0.05,
+
+			recoil_recovery: 8.0,
+			recoil_moves_aim: settings.recoil_moves_aim,
+			recoil: (Deg(0.0), Deg(0.0)).into(),
+
+			landing_stiffness: 120.0,
+			landing_damping: 14.0,
+			landing_offset: 0.0,
+			landing_velocity: 0.0,
+
+			time: 0.0,
+		};
+	}
+
+	pub fn add_trauma(&mut self, amount: f32) {
+		self.trauma = (self.trauma + amount).clamp(0.0, 1.0);
+		return;
+	}
+
+	// yaw, pitch
+	pub fn kick(&mut self, camera: &mut Camera, player: &mut Player, kick: Vector2<Deg<f32>>) {
+		if self.recoil_moves_aim {
+			camera.rot.x += kick.x;
+			camera.rot.y = pitch_clamp(camera.rot.y.0 + kick.y.0);
+			player.rot_x += kick.x;
+		} else {
+			self.recoil = Vector2::new(self.recoil.x + kick.x, self.recoil.y + kick.y);
+		}
+		return;
+	}
+
+	// impact_speed in units/s, positive downwards
+	pub fn land(&mut self, impact_speed: f32) {
+		self.landing_velocity -= impact_speed * 0.1;
+		return;
+	}
+
+	pub fn update(&mut self, dt: f32) {
+		self.time += dt;
+		self.trauma = (self.trauma - self.trauma_decay * dt).max(0.0);
+
+		let recovery = (-self.recoil_recovery * dt).exp();
+		self.recoil = Vector2::new(self.recoil.x * recovery, self.recoil.y * recovery);
+
+		let accel = -self.landing_stiffness * self.landing_offset - self.landing_damping * self.landing_velocity;
+		self.landing_velocity += accel * dt;
+		self.landing_offset += self.landing_velocity * dt;
+
+		return;
+	}
+
+	// run after Camera::update_rot and Camera::set_pos
+	pub fn apply(&self, camera: &mut Camera) {
+		let shake = self.trauma * self.trauma;
+		let t = self.time * self.shake_frequency;
+
+		camera.rot_offset = Vector2::new(
+			self.max_shake_rot.x * (shake * noise(0, t)) + self.recoil.x,
+			self.max_shake_rot.y * (shake * noise(1, t)) + self.recoil.y,
+		);
+		camera.pos_offset = Vector3::new(
+			self.max_shake_offset * shake * noise(2, t),
+			self.landing_offset,
+			0.0,
+		);
+
+		return;
+	}
+}
@@ -0,0 +1,195 @@
+use std::path::Path;
+use cgmath::{Point3, Deg, Vector2, Vector3, Quaternion, Rotation, Rotation3, InnerSpace, EuclideanSpace};
+use crate::camera::Camera;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Keyframe {
+	pub time: f32,
+	pub position: Point3<f32>,
+	pub rot: Vector2<Deg<f32>>,
+}
+
+// the camera only knows yaw/pitch, but interpolating those directly
+// takes the long way around, so rotations are slerped as quaternions.
+// yaw 0, pitch 0 looks down +x (see CameraUniform::set_view_projection_matrix).
+fn rot_to_quaternion(rot: Vector2<Deg<f32>>) -> Quaternion<f32> {
+	let yaw = Quaternion::from_angle_y(-rot.x);
+	let pitch = Quaternion::from_angle_z(rot.y);
+	return yaw * pitch;
+}
+fn quaternion_to_rot(q: Quaternion<f32>) -> Vector2<Deg<f32>> {
+	let forward = q.rotate_vector(Vector3::unit_x()).normalize();
+	let yaw = Deg::from(cgmath::Rad(forward.z.atan2(forward.x)));
+	let pitch = Deg::from(cgmath::Rad(forward.y.clamp(-1.0, 1.0).asin()));
+	return Vector2::new(yaw, crate::camera::pitch_clamp(pitch.0));
+}
+
+fn catmull_rom(p0: Point3<f32>, p1: Point3<f32>, p2: Point3<f32>, p3: Point3<f32>, t: f32) -> Point3<f32> {
+	let (p0, p1, p2, p3) = (p0.to_vec(), p1.to_vec(), p2.to_vec(), p3.to_vec());
+	let t2 = t * t;
+	let t3 = t2 * t;
+	let v = (p1 * 2.0
+		+ (p2 - p0) * t
+		+ (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * t2
+		+ (p1 * 3.0 - p0 - p2 * 3.0 + p3) * t3) * 0.5;
+	return Point3::from_vec(v);
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CameraPath {
+	pub keyframes: Vec<Keyframe>,
+}
+
+impl CameraPath {
+	pub fn duration(&self) -> f32 {
+		return self.keyframes.last().map(|k| k.time).unwrap_or(0.0);
+	}
+
+	pub fn sample(&self, time: f32) -> Option<(Point3<f32>, Vector2<Deg<f32>>)> {
+		let keyframes = &(self.keyframes);
+		let last = keyframes.len().checked_sub(1)?;
+		if time <= keyframes[0].time || last == 0 {
+			return Some((keyframes[0].position, keyframes[0].rot));
+		}
+		if time >= keyframes[last].time {
+			return Some((keyframes[last].position, keyframes[last].rot));
+		}
+
+		// segment [i, i + 1] contains time
+		let i = keyframes.partition_point(|k| k.time <= time) - 1;
+		let k1 = &(keyframes[i]);
+		let k2 = &(keyframes[i + 1]);
+		let k0 = &(keyframes[i.saturating_sub(1)]);
+		let k3 = &(keyframes[(i + 2).min(last)]);
+
+		let span = k2.time - k1.time;
+		let t = if span > 0.0 { (time - k1.time) / span } else { 0.0 };
+
+		let position = catmull_rom(k0.position, k1.position, k2.position, k3.position, t);
+		let rot = quaternion_to_rot(rot_to_quaternion(k1.rot).slerp(rot_to_quaternion(k2.rot), t));
+		return Some((position, rot));
+	}
+
+	// one line per keyframe: time x y z yaw pitch
+	pub fn save<T: AsRef<Path>>(&self, file_name: T) -> Result<(), &'static str> {
+		let mut out = String::new();
+		for k in &(self.keyframes) {
+			out += &(format!(
+				"{} {} {} {} {} {}\n",
+				k.time, k.position.x, k.position.y, k.position.z, k.rot.x.0, k.rot.y.0
+			));
+		}
+		return std::fs::write(file_name, out).map_err(|_| "failed to write camera path");
+	}
+
+	pub fn load<T: AsRef<Path>>(file_name: T) -> Result<Self, &'static str> {
+		let text = std::fs::read_to_string(file_name).map_err(|_| "failed to read camera path")?;
+
+		let mut keyframes = Vec::new();
+		for line in text.lines().filter(|line| !line.trim().is_empty()) {
+			let values = line
+				.split_whitespace()
+				.map(|v| v.parse::<f32>())
+				.collect::<Result<Vec<f32>, _>>()
+				.map_err(|_| "invalid number in camera path")?;
+			let [time, x, y, z, yaw, pitch] = values[..] else {
+				return Err("camera path keyframe needs 6 values");
+			};
+			keyframes.push(Keyframe {
+				time,
+				position: (x, y, z).into(),
+				rot: (Deg(yaw), Deg(pitch)).into(),
+			});
+		}
+		if keyframes.windows(2).any(|w| w[1].time < w[0].time) {
+			return Err("camera path keyframes are not in order");
+		}
+
+		return Ok(Self { keyframes });
+	}
+}
+
+pub struct CameraRecorder {
+	pub path: CameraPath,
+	pub interval: f32,
+	elapsed: f32,
+	since_keyframe: f32,
+}
+
+impl CameraRecorder {
+	pub fn new(interval: f32) -> Self {
+		return Self {
+			path: CameraPath::default(),
+			interval,
+			elapsed: 0.0,
+			since_keyframe: f32::INFINITY,
+		};
+	}
+
+	pub fn record(&mut self, dt: f32, camera: &Camera) {
+		self.since_keyframe += dt;
+		if self.since_keyframe >= self.interval {
+			if let Some(position) = camera.position {
+				self.path.keyframes.push(Keyframe { time: self.elapsed, position, rot: camera.rot });
+			}
+			self.since_keyframe = 0.0;
+		}
+		self.elapsed += dt;
+		return;
+	}
+}
+
+pub struct CameraPlayback {
+	pub path: CameraPath,
+	pub camera: Camera,
+	pub speed: f32,
+	pub looping: bool,
+	time: f32,
+}
+
+impl CameraPlayback {
+	pub fn new(path: CameraPath, camera: &Camera) -> Self {
+		let mut camera = camera.clone();
+		camera.clear_offsets();
+		let mut playback = Self {
+			path,
+			camera,
+			speed: 1.0,
+			looping: false,
+			time: 0.0,
+		};
+		playback.seek(0.0);
+		return playback;
+	}
+
+	pub fn is_finished(&self) -> bool {
+		return !self.looping && self.time >= self.path.duration();
+	}
+
+	pub fn seek(&mut self, time: f32) {
+		self.time = time;
+		if let Some((position, rot)) = self.path.sample(time) {
+			self.camera.set_pos(position);
+			self.camera.rot = rot;
+		}
+		return;
+	}
+
+	pub fn update(&mut self, dt: f32) {
+		let duration = self.path.duration();
+		let mut time = self.time + dt * self.speed;
+		if self.looping && duration > 0.0 {
+			time = time.rem_euclid(duration);
+		}
+		self.seek(time.min(duration));
+		return;
+	}
+
+	// fixed-rate sample times for rendering image sequences,
+	// independent of how fast frames are actually produced.
+	pub fn frame_times(&self, fps: f32) -> impl Iterator<Item = f32> {
+		let step = self.speed / fps;
+		let count = (self.path.duration() / step).floor() as usize + 1;
+		return (0..count).map(move |i| i as f32 * step);
+	}
+}
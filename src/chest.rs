@@ -0,0 +1,95 @@
+use cgmath::{Point3, Vector3, InnerSpace};
+
+use crate::{
+	collision::{CollisionWorld, ColliderId, cuboid},
+	obj::Vertex,
+};
+
+// width, height, depth
+const SIZE: Vector3<f32> = Vector3::new(1.0, 0.7, 0.6);
+// seconds interact has to be held to open one
+pub const OPEN_TIME: f32 = 1.0;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Chest {
+	// centre of its base
+	pub position: Point3<f32>,
+	pub opened: bool,
+	pub collider: ColliderId,
+}
+
+pub type ChestId = usize;
+
+pub struct Chests {
+	chests: Vec<Chest>,
+	// bumped on every change, see Buildings
+	generation: u64,
+}
+
+impl Chests {
+	pub fn new() -> Self {
+		return Self { chests: Vec::new(), generation: 0 };
+	}
+
+	pub fn generation(&self) -> u64 {
+		return self.generation;
+	}
+
+	pub fn get(&self, id: ChestId) -> Option<&Chest> {
+		return self.chests.get(id);
+	}
+
+	pub fn spawn(&mut self, position: Point3<f32>, collision: &mut CollisionWorld) -> ChestId {
+		let collider = collision.add_dynamic(cuboid(position, SIZE));
+		self.chests.push(Chest { position, opened: false, collider });
+		self.generation += 1;
+		return self.chests.len() - 1;
+	}
+
+	pub fn clear(&mut self, collision: &mut CollisionWorld) {
+		for chest in self.chests.drain(..) {
+			collision.remove_dynamic(chest.collider);
+		}
+		self.generation += 1;
+		return;
+	}
+
+	// the closest unopened one no further than `reach` from `position`
+	pub fn nearest(&self, position: Point3<f32>, reach: f32) -> Option<ChestId> {
+		return self.chests
+			.iter()
+			.enumerate()
+			.filter(|(_, chest)| !chest.opened)
+			.map(|(id, chest)| (id, (chest.position - position).magnitude()))
+			.filter(|&(_, distance)| distance <= reach)
+			.min_by(|(_, a), (_, b)| a.total_cmp(b))
+			.map(|(id, _)| id);
+	}
+
+	// whether it was still closed
+	pub fn open(&mut self, id: ChestId) -> bool {
+		let Some(chest) = self.chests.get_mut(id) else {
+			return false;
+		};
+		if chest.opened {
+			return false;
+		}
+		chest.opened = true;
+		self.generation += 1;
+		return true;
+	}
+
+	// closed, then opened
+	pub fn vertices(&self) -> [Vec<Vertex>; 2] {
+		let mut vertices = [Vec::new(), Vec::new()];
+		for chest in &(self.chests) {
+			let group = &mut(vertices[chest.opened as usize]);
+			for triangle in cuboid(chest.position, SIZE) {
+				for position in [triangle.a, triangle.b, triangle.c] {
+					group.push(Vertex { position: position.into(), tex_coords: [0.0, 0.0] });
+				}
+			}
+		}
+		return vertices;
+	}
+}
@@ -0,0 +1,763 @@
+use std::cell::Cell;
+use cgmath::{Point3, Vector3, InnerSpace, EuclideanSpace, Zero};
+
+use crate::player::{Ground, GroundHit};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Triangle {
+	pub a: Point3<f32>,
+	pub b: Point3<f32>,
+	pub c: Point3<f32>,
+}
+
+impl Triangle {
+	pub fn new(a: Point3<f32>, b: Point3<f32>, c: Point3<f32>) -> Self {
+		return Self { a, b, c };
+	}
+
+	pub fn normal(&self) -> Vector3<f32> {
+		let n = (self.b - self.a).cross(self.c - self.a);
+		if n.magnitude2() == 0.0 {
+			return Vector3::unit_y();
+		}
+		return n.normalize();
+	}
+
+	pub fn aabb(&self) -> Aabb {
+		return Aabb::from_point(self.a).with_point(self.b).with_point(self.c);
+	}
+
+	fn centroid(&self) -> Point3<f32> {
+		return Point3::from_vec((self.a.to_vec() + self.b.to_vec() + self.c.to_vec()) / 3.0);
+	}
+
+	// moller-trumbore, hits both sides
+	pub fn raycast(&self, origin: Point3<f32>, dir: Vector3<f32>) -> Option<f32> {
+		let e1 = self.b - self.a;
+		let e2 = self.c - self.a;
+		let p = dir.cross(e2);
+		let det = e1.dot(p);
+		if det.abs() < 1e-8 {
+			return None;
+		}
+		let inv_det = 1.0 / det;
+		let s = origin - self.a;
+		let u = s.dot(p) * inv_det;
+		if !(0.0..=1.0).contains(&u) {
+			return None;
+		}
+		let q = s.cross(e1);
+		let v = dir.dot(q) * inv_det;
+		if v < 0.0 || u + v > 1.0 {
+			return None;
+		}
+		let t = e2.dot(q) * inv_det;
+		return if t >= 0.0 { Some(t) } else { None };
+	}
+
+	// from real-time collision detection, 5.1.5
+	pub fn closest_point(&self, p: Point3<f32>) -> Point3<f32> {
+		let (a, b, c) = (self.a, self.b, self.c);
+		let ab = b - a;
+		let ac = c - a;
+		let ap = p - a;
+		let d1 = ab.dot(ap);
+		let d2 = ac.dot(ap);
+		if d1 <= 0.0 && d2 <= 0.0 {
+			return a;
+		}
+		let bp = p - b;
+		let d3 = ab.dot(bp);
+		let d4 = ac.dot(bp);
+		if d3 >= 0.0 && d4 <= d3 {
+			return b;
+		}
+		let vc = d1 * d4 - d3 * d2;
+		if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+			return a + ab * (d1 / (d1 - d3));
+		}
+		let cp = p - c;
+		let d5 = ab.dot(cp);
+		let d6 = ac.dot(cp);
+		if d6 >= 0.0 && d5 <= d6 {
+			return c;
+		}
+		let vb = d5 * d2 - d1 * d6;
+		if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+			return a + ac * (d2 / (d2 - d6));
+		}
+		let va = d3 * d6 - d5 * d4;
+		if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+			return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+		}
+		let denom = 1.0 / (va + vb + vc);
+		return a + ab * (vb * denom) + ac * (vc * denom);
+	}
+
+	// closest points between the segment p..q and the triangle, (on segment, on triangle)
+	pub fn closest_to_segment(&self, p: Point3<f32>, q: Point3<f32>) -> (Point3<f32>, Point3<f32>) {
+		// the segment passes through the triangle
+		let dir = q - p;
+		if let Some(t) = self.raycast(p, dir) {
+			if t <= 1.0 {
+				let point = p + dir * t;
+				return (point, point);
+			}
+		}
+
+		let mut best = (p, self.closest_point(p));
+		let mut best_dist = (best.0 - best.1).magnitude2();
+		let mut consider = |pair: (Point3<f32>, Point3<f32>)| {
+			let dist = (pair.0 - pair.1).magnitude2();
+			if dist < best_dist {
+				best = pair;
+				best_dist = dist;
+			}
+		};
+		consider((q, self.closest_point(q)));
+		for (e0, e1) in [(self.a, self.b), (self.b, self.c), (self.c, self.a)] {
+			consider(closest_segment_segment(p, q, e0, e1));
+		}
+		return best;
+	}
+}
+
+// a box standing on `base` (the centre of its bottom face), wound counter-clockwise from outside
+pub fn cuboid(base: Point3<f32>, size: Vector3<f32>) -> Vec<Triangle> {
+	let min = base - Vector3::new(size.x / 2.0, 0.0, size.z / 2.0);
+	let center = base + Vector3::unit_y() * (size.y / 2.0);
+	let corner = |x: f32, y: f32, z: f32| min + Vector3::new(x * size.x, y * size.y, z * size.z);
+
+	let mut triangles = Vec::new();
+	for axis in 0..3 {
+		for side in [0.0, 1.0] {
+			// the four corners of this face, going round it
+			let quad = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)].map(|(a, b)| match axis {
+				0 => corner(side, a, b),
+				1 => corner(b, side, a),
+				_ => corner(a, b, side),
+			});
+			let mut a = Triangle::new(quad[0], quad[1], quad[2]);
+			let mut b = Triangle::new(quad[0], quad[2], quad[3]);
+			if a.normal().dot(quad[0] - center) < 0.0 {
+				a = Triangle::new(quad[0], quad[2], quad[1]);
+				b = Triangle::new(quad[0], quad[3], quad[2]);
+			}
+			triangles.extend_from_slice(&[a, b]);
+		}
+	}
+	return triangles;
+}
+
+// from real-time collision detection, 5.1.9
+fn closest_segment_segment(p1: Point3<f32>, q1: Point3<f32>, p2: Point3<f32>, q2: Point3<f32>) -> (Point3<f32>, Point3<f32>) {
+	let d1 = q1 - p1;
+	let d2 = q2 - p2;
+	let r = p1 - p2;
+	let a = d1.magnitude2();
+	let e = d2.magnitude2();
+	let f = d2.dot(r);
+	const EPSILON: f32 = 1e-8;
+
+	let (s, t);
+	if a <= EPSILON && e <= EPSILON {
+		return (p1, p2);
+	}
+	if a <= EPSILON {
+		s = 0.0;
+		t = (f / e).clamp(0.0, 1.0);
+	} else {
+		let c = d1.dot(r);
+		if e <= EPSILON {
+			t = 0.0;
+			s = (-c / a).clamp(0.0, 1.0);
+		} else {
+			let b = d1.dot(d2);
+			let denom = a * e - b * b;
+			let mut s_ = if denom != 0.0 { ((b * f - c * e) / denom).clamp(0.0, 1.0) } else { 0.0 };
+			let mut t_ = (b * s_ + f) / e;
+			if t_ < 0.0 {
+				t_ = 0.0;
+				s_ = (-c / a).clamp(0.0, 1.0);
+			} else if t_ > 1.0 {
+				t_ = 1.0;
+				s_ = ((b - c) / a).clamp(0.0, 1.0);
+			}
+			s = s_;
+			t = t_;
+		}
+	}
+	return (p1 + d1 * s, p2 + d2 * t);
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Aabb {
+	pub min: Point3<f32>,
+	pub max: Point3<f32>,
+}
+
+impl Aabb {
+	pub fn from_point(p: Point3<f32>) -> Self {
+		return Self { min: p, max: p };
+	}
+
+	pub fn with_point(self, p: Point3<f32>) -> Self {
+		return Self {
+			min: Point3::new(self.min.x.min(p.x), self.min.y.min(p.y), self.min.z.min(p.z)),
+			max: Point3::new(self.max.x.max(p.x), self.max.y.max(p.y), self.max.z.max(p.z)),
+		};
+	}
+
+	pub fn union(self, other: Aabb) -> Self {
+		return self.with_point(other.min).with_point(other.max);
+	}
+
+	pub fn expand(self, amount: f32) -> Self {
+		let v = Vector3::new(amount, amount, amount);
+		return Self { min: self.min - v, max: self.max + v };
+	}
+
+	pub fn intersects(&self, other: &Aabb) -> bool {
+		return self.min.x <= other.max.x && self.max.x >= other.min.x
+			&& self.min.y <= other.max.y && self.max.y >= other.min.y
+			&& self.min.z <= other.max.z && self.max.z >= other.min.z;
+	}
+
+	// slab test, whether the ray enters the box before max_t
+	pub fn ray_hits(&self, origin: Point3<f32>, inv_dir: Vector3<f32>, max_t: f32) -> bool {
+		let mut t_min = 0.0f32;
+		let mut t_max = max_t;
+		for axis in 0..3 {
+			let t1 = (self.min[axis] - origin[axis]) * inv_dir[axis];
+			let t2 = (self.max[axis] - origin[axis]) * inv_dir[axis];
+			// NaN (0 * inf) means the ray lies on the slab's plane, which doesn't rule anything out
+			if t1.is_nan() || t2.is_nan() {
+				continue;
+			}
+			t_min = t_min.max(t1.min(t2));
+			t_max = t_max.min(t1.max(t2));
+		}
+		return t_min <= t_max;
+	}
+
+	fn longest_axis(&self) -> usize {
+		let size = self.max - self.min;
+		if size.x >= size.y && size.x >= size.z {
+			return 0;
+		}
+		return if size.y >= size.z { 1 } else { 2 };
+	}
+}
+
+// leaves hold up to this many triangles
+const BVH_LEAF_SIZE: usize = 4;
+
+#[derive(Debug, Clone)]
+struct BvhNode {
+	aabb: Aabb,
+	// leaf: triangles[start..start + count], otherwise children at start and start + 1
+	start: u32,
+	count: u32,
+}
+
+// bounding volume hierarchy over a fixed set of triangles
+#[derive(Debug, Clone)]
+pub struct Bvh {
+	nodes: Vec<BvhNode>,
+	// triangle indices, ordered so each leaf is a contiguous range
+	indices: Vec<u32>,
+}
+
+impl Bvh {
+	pub fn build(triangles: &[Triangle]) -> Self {
+		let mut bvh = Self {
+			nodes: Vec::new(),
+			indices: (0..triangles.len() as u32).collect(),
+		};
+		if !triangles.is_empty() {
+			let centroids = triangles.iter().map(|t| t.centroid()).collect::<Vec<_>>();
+			bvh.nodes.push(BvhNode { aabb: triangles[0].aabb(), start: 0, count: 0 });
+			bvh.build_node(0, 0, triangles.len(), triangles, &(centroids));
+		}
+		return bvh;
+	}
+
+	fn build_node(&mut self, node: usize, start: usize, end: usize, triangles: &[Triangle], centroids: &[Point3<f32>]) {
+		let indices = &mut(self.indices[start..end]);
+		let aabb = indices
+			.iter()
+			.map(|&i| triangles[i as usize].aabb())
+			.reduce(Aabb::union)
+			.unwrap();
+		self.nodes[node].aabb = aabb;
+
+		if end - start <= BVH_LEAF_SIZE {
+			self.nodes[node].start = start as u32;
+			self.nodes[node].count = (end - start) as u32;
+			return;
+		}
+
+		// median split along the longest axis of the centroids
+		let centroid_aabb = indices
+			.iter()
+			.map(|&i| Aabb::from_point(centroids[i as usize]))
+			.reduce(Aabb::union)
+			.unwrap();
+		let axis = centroid_aabb.longest_axis();
+		let mid = (end - start) / 2;
+		indices.select_nth_unstable_by(mid, |&x, &y| {
+			centroids[x as usize][axis].total_cmp(&(centroids[y as usize][axis]))
+		});
+
+		let left = self.nodes.len();
+		self.nodes[node].start = left as u32;
+		self.nodes[node].count = 0;
+		self.nodes.push(BvhNode { aabb, start: 0, count: 0 });
+		self.nodes.push(BvhNode { aabb, start: 0, count: 0 });
+		self.build_node(left, start, start + mid, triangles, centroids);
+		self.build_node(left + 1, start + mid, end, triangles, centroids);
+		return;
+	}
+
+	// calls visit with every triangle index in leaves whose boxes pass `test`
+	pub fn traverse<T: FnMut(&Aabb) -> bool, V: FnMut(usize)>(&self, mut test: T, mut visit: V) {
+		if self.nodes.is_empty() {
+			return;
+		}
+		let mut stack = vec![0usize];
+		while let Some(node) = stack.pop() {
+			let node = &(self.nodes[node]);
+			if !test(&(node.aabb)) {
+				continue;
+			}
+			if node.count > 0 {
+				let start = node.start as usize;
+				for &i in &(self.indices[start..start + node.count as usize]) {
+					visit(i as usize);
+				}
+			} else {
+				stack.push(node.start as usize);
+				stack.push(node.start as usize + 1);
+			}
+		}
+		return;
+	}
+}
+
+// distance along a normalized ray to a sphere, 0 if starting inside it
+pub fn ray_sphere(origin: Point3<f32>, dir: Vector3<f32>, center: Point3<f32>, radius: f32) -> Option<f32> {
+	let m = origin - center;
+	let b = m.dot(dir);
+	let c = m.dot(m) - radius * radius;
+	if c > 0.0 && b > 0.0 {
+		return None;
+	}
+	let discriminant = b * b - c;
+	if discriminant < 0.0 {
+		return None;
+	}
+	return Some((-b - discriminant.sqrt()).max(0.0));
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColliderId {
+	// map geometry, never changes
+	Static,
+	Dynamic(usize),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Capsule {
+	// centres of the two end spheres
+	pub a: Point3<f32>,
+	pub b: Point3<f32>,
+	pub radius: f32,
+}
+
+impl Capsule {
+	// an upright capsule standing at `feet`
+	pub fn standing(feet: Point3<f32>, radius: f32, height: f32) -> Self {
+		let radius = radius.min(height / 2.0);
+		return Self {
+			a: feet + Vector3::unit_y() * radius,
+			b: feet + Vector3::unit_y() * (height - radius),
+			radius,
+		};
+	}
+
+	pub fn aabb(&self) -> Aabb {
+		return Aabb::from_point(self.a).with_point(self.b).expand(self.radius);
+	}
+
+	// distance along a normalized ray to the surface: the cylinder between the
+	// end spheres, or either sphere, whichever is first
+	pub fn raycast(&self, origin: Point3<f32>, dir: Vector3<f32>) -> Option<f32> {
+		let mut best = ray_sphere(origin, dir, self.a, self.radius);
+		let end = ray_sphere(origin, dir, self.b, self.radius);
+		if end.is_some_and(|t| best.is_none_or(|best| t < best)) {
+			best = end;
+		}
+
+		// from inigo quilez's capsule intersection
+		let ba = self.b - self.a;
+		let oa = origin - self.a;
+		let baba = ba.dot(ba);
+		let bard = ba.dot(dir);
+		let baoa = ba.dot(oa);
+		let a = baba - bard * bard;
+		let b = baba * dir.dot(oa) - baoa * bard;
+		let c = baba * oa.dot(oa) - baoa * baoa - self.radius * self.radius * baba;
+		let h = b * b - a * c;
+		if a > 1e-6 && h >= 0.0 {
+			let t = (-b - h.sqrt()) / a;
+			let y = baoa + t * bard;
+			if t >= 0.0 && y > 0.0 && y < baba && best.is_none_or(|best| t < best) {
+				best = Some(t);
+			}
+		}
+		return best;
+	}
+
+	fn translated(&self, by: Vector3<f32>) -> Self {
+		return Self { a: self.a + by, b: self.b + by, radius: self.radius };
+	}
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RayHit {
+	pub distance: f32,
+	pub point: Point3<f32>,
+	pub normal: Vector3<f32>,
+	pub collider: ColliderId,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SweepHit {
+	// fraction of the motion completed before touching
+	pub time: f32,
+	pub point: Point3<f32>,
+	pub normal: Vector3<f32>,
+	pub collider: ColliderId,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Overlap {
+	pub collider: ColliderId,
+	// direction to push the capsule out along, and how far
+	pub normal: Vector3<f32>,
+	pub depth: f32,
+}
+
+struct Mesh {
+	triangles: Vec<Triangle>,
+	bvh: Bvh,
+}
+
+impl Mesh {
+	fn new(triangles: Vec<Triangle>) -> Self {
+		let bvh = Bvh::build(&(triangles));
+		return Self { triangles, bvh };
+	}
+}
+
+// everything that can be collided with.
+// static geometry is one bvh built once, dynamic colliders (e.g. built
+// pieces) each get their own small bvh so they can be changed cheaply.
+pub struct CollisionWorld {
+	static_mesh: Mesh,
+	dynamic: Vec<Option<Mesh>>,
+}
+
+// separation kept from surfaces after a sweep, so the next sweep doesn't start touching
+const SKIN: f32 = 0.001;
+
+impl CollisionWorld {
+	pub fn new(static_triangles: Vec<Triangle>) -> Self {
+		return Self {
+			static_mesh: Mesh::new(static_triangles),
+			dynamic: Vec::new(),
+		};
+	}
+
+	pub fn add_dynamic(&mut self, triangles: Vec<Triangle>) -> ColliderId {
+		self.dynamic.push(Some(Mesh::new(triangles)));
+		return ColliderId::Dynamic(self.dynamic.len() - 1);
+	}
+
+	pub fn update_dynamic(&mut self, id: ColliderId, triangles: Vec<Triangle>) {
+		if let ColliderId::Dynamic(index) = id {
+			self.dynamic[index] = Some(Mesh::new(triangles));
+		}
+		return;
+	}
+
+	pub fn remove_dynamic(&mut self, id: ColliderId) {
+		if let ColliderId::Dynamic(index) = id {
+			self.dynamic[index] = None;
+		}
+		return;
+	}
+
+	fn meshes(&self) -> impl Iterator<Item = (ColliderId, &Mesh)> {
+		let dynamic = self.dynamic
+			.iter()
+			.enumerate()
+			.filter_map(|(i, mesh)| mesh.as_ref().map(|mesh| (ColliderId::Dynamic(i), mesh)));
+		return std::iter::once((ColliderId::Static, &(self.static_mesh))).chain(dynamic);
+	}
+
+	pub fn raycast(&self, origin: Point3<f32>, dir: Vector3<f32>, max_distance: f32) -> Option<RayHit> {
+		let dir = dir.normalize();
+		let inv_dir = Vector3::new(1.0 / dir.x, 1.0 / dir.y, 1.0 / dir.z);
+		let best = Cell::new(max_distance);
+		let mut hit = None;
+		for (collider, mesh) in self.meshes() {
+			mesh.bvh.traverse(
+				|aabb| aabb.ray_hits(origin, inv_dir, best.get()),
+				|i| {
+					let triangle = &(mesh.triangles[i]);
+					if let Some(t) = triangle.raycast(origin, dir) {
+						if t < best.get() {
+							best.set(t);
+							let mut normal = triangle.normal();
+							if normal.dot(dir) > 0.0 {
+								normal = -normal;
+							}
+							hit = Some(RayHit { distance: t, point: origin + dir * t, normal, collider });
+						}
+					}
+				},
+			);
+		}
+		return hit;
+	}
+
+	// closest point on any triangle within `radius` of the capsule's segment
+	fn capsule_contact(mesh: &Mesh, capsule: &Capsule, i: usize) -> Option<(f32, Point3<f32>, Vector3<f32>)> {
+		let triangle = &(mesh.triangles[i]);
+		let (on_segment, on_triangle) = triangle.closest_to_segment(capsule.a, capsule.b);
+		let offset = on_segment - on_triangle;
+		let distance = offset.magnitude();
+		if distance >= capsule.radius {
+			return None;
+		}
+		let normal = if distance > 1e-6 { offset / distance } else { triangle.normal() };
+		return Some((distance, on_triangle, normal));
+	}
+
+	pub fn overlap_capsule(&self, capsule: &Capsule) -> Vec<Overlap> {
+		let aabb = capsule.aabb();
+		let mut overlaps = Vec::new();
+		for (collider, mesh) in self.meshes() {
+			mesh.bvh.traverse(
+				|node| node.intersects(&(aabb)),
+				|i| {
+					if let Some((distance, _, normal)) = Self::capsule_contact(mesh, capsule, i) {
+						overlaps.push(Overlap { collider, normal, depth: capsule.radius - distance });
+					}
+				},
+			);
+		}
+		return overlaps;
+	}
+
+	// conservative advancement: the gap between two convex shapes is convex
+	// in t, so it can't close faster than it's closing right now. step by
+	// that until touching.
+	fn sweep_triangle(triangle: &Triangle, capsule: &Capsule, motion: Vector3<f32>) -> Option<(f32, Point3<f32>, Vector3<f32>)> {
+		let mut t = 0.0;
+		let mut closest = (triangle.a, triangle.normal());
+		for _ in 0..32 {
+			let moved = capsule.translated(motion * t);
+			let (on_segment, on_triangle) = triangle.closest_to_segment(moved.a, moved.b);
+			let offset = on_segment - on_triangle;
+			let gap = offset.magnitude() - capsule.radius;
+			let normal = if offset.magnitude2() > 1e-12 { offset.normalize() } else { triangle.normal() };
+			let closing = -motion.dot(normal);
+			if gap <= SKIN {
+				// already touching but moving away, let it go
+				if closing <= 0.0 {
+					return None;
+				}
+				return Some((t, on_triangle, normal));
+			}
+			// moving along it or away from it, it never gets any closer
+			if closing <= 0.0 {
+				return None;
+			}
+			// aim to stop half a skin short, so it lands inside the skin
+			t += (gap - SKIN / 2.0) / closing;
+			if t > 1.0 {
+				return None;
+			}
+			closest = (on_triangle, normal);
+		}
+		// still creeping up on it, e.g. round an edge. every step so far was
+		// safe, so stop there rather than letting it pass through
+		let (on_triangle, normal) = closest;
+		return Some((t, on_triangle, normal));
+	}
+
+	pub fn sweep_capsule(&self, capsule: &Capsule, motion: Vector3<f32>) -> Option<SweepHit> {
+		let aabb = capsule.aabb().union(capsule.translated(motion).aabb());
+		let mut hit: Option<SweepHit> = None;
+		for (collider, mesh) in self.meshes() {
+			mesh.bvh.traverse(
+				|node| node.intersects(&(aabb)),
+				|i| {
+					if let Some((time, point, normal)) = Self::sweep_triangle(&(mesh.triangles[i]), capsule, motion) {
+						if hit.is_none_or(|hit| time < hit.time) {
+							hit = Some(SweepHit { time, point, normal, collider });
+						}
+					}
+				},
+			);
+		}
+		return hit;
+	}
+}
+
+impl Ground for CollisionWorld {
+	fn ground_below(&self, position: Point3<f32>, radius: f32, max_up: f32, max_down: f32) -> Option<GroundHit> {
+		// drop a sphere from max_up above the feet
+		let start = position + Vector3::unit_y() * (max_up + radius);
+		let sphere = Capsule { a: start, b: start, radius };
+		let hit = self.sweep_capsule(&(sphere), -Vector3::unit_y() * (max_up + max_down))?;
+		return Some(GroundHit { height: hit.point.y, normal: hit.normal });
+	}
+
+	fn sweep_body(&self, position: Point3<f32>, radius: f32, height: f32, motion: Vector3<f32>) -> Option<(f32, Vector3<f32>)> {
+		if motion.is_zero() {
+			return None;
+		}
+		let hit = self.sweep_capsule(&(Capsule::standing(position, radius, height)), motion)?;
+		return Some((hit.time, hit.normal));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// two triangles going round a b c d
+	fn quad(a: Point3<f32>, b: Point3<f32>, c: Point3<f32>, d: Point3<f32>) -> Vec<Triangle> {
+		return vec![Triangle::new(a, b, c), Triangle::new(a, c, d)];
+	}
+
+	fn close(a: Vector3<f32>, b: Vector3<f32>) -> bool {
+		return (a - b).magnitude() < 1e-3;
+	}
+
+	// a 1x1 slab with its top at y = 0, centred on the origin
+	fn floor() -> CollisionWorld {
+		return CollisionWorld::new(cuboid((0.0, -1.0, 0.0).into(), (1.0, 1.0, 1.0).into()));
+	}
+
+	#[test]
+	fn rays_hit_and_miss_a_triangle() {
+		let triangle = Triangle::new((0.0, 0.0, 0.0).into(), (1.0, 0.0, 0.0).into(), (0.0, 0.0, 1.0).into());
+		let down = -Vector3::unit_y();
+		assert_eq!(triangle.raycast((0.25, 2.0, 0.25).into(), down), Some(2.0));
+		// both sides
+		assert_eq!(triangle.raycast((0.25, -2.0, 0.25).into(), -down), Some(2.0));
+		// past the long edge, pointing away, and running alongside it
+		assert_eq!(triangle.raycast((0.75, 2.0, 0.75).into(), down), None);
+		assert_eq!(triangle.raycast((0.25, 2.0, 0.25).into(), -down), None);
+		assert_eq!(triangle.raycast((-1.0, 0.0, 0.25).into(), Vector3::unit_x()), None);
+
+		let world = CollisionWorld::new(vec![triangle]);
+		let hit = world.raycast((0.25, 2.0, 0.25).into(), down * 5.0, 10.0).unwrap();
+		assert!((hit.distance - 2.0).abs() < 1e-6);
+		assert!(close(hit.normal, Vector3::unit_y()));
+		assert_eq!(hit.collider, ColliderId::Static);
+		assert!(world.raycast((0.25, 2.0, 0.25).into(), down, 1.5).is_none());
+		return;
+	}
+
+	#[test]
+	fn sweep_stops_at_a_wall() {
+		// the near face is at x = 2
+		let world = CollisionWorld::new(cuboid((2.5, 0.0, 0.0).into(), (1.0, 5.0, 10.0).into()));
+		let capsule = Capsule::standing((0.0, 0.0, 0.0).into(), 0.5, 2.0);
+		let hit = world.sweep_capsule(&(capsule), Vector3::new(4.0, 0.0, 0.0)).unwrap();
+		// stops within the skin of touching
+		let travelled = hit.time * 4.0;
+		assert!((1.5 - SKIN..=1.5).contains(&(travelled)), "{travelled}");
+		assert!(close(hit.normal, -Vector3::unit_x()));
+		assert!((hit.point.x - 2.0).abs() < 1e-6);
+
+		// short of it, and backing off from it
+		assert!(world.sweep_capsule(&(capsule), Vector3::new(1.0, 0.0, 0.0)).is_none());
+		let touching = Capsule::standing((1.5, 0.0, 0.0).into(), 0.5, 2.0);
+		assert!(world.sweep_capsule(&(touching), Vector3::new(-1.0, 0.0, 0.0)).is_none());
+		return;
+	}
+
+	#[test]
+	fn sliding_along_a_wall_isnt_stopped() {
+		let world = CollisionWorld::new(cuboid((2.5, 0.0, 0.0).into(), (1.0, 5.0, 10.0).into()));
+		// barely off the wall, but going parallel to it
+		let capsule = Capsule::standing((1.495, 0.0, -2.0).into(), 0.5, 2.0);
+		assert!(world.sweep_capsule(&(capsule), Vector3::new(0.0, 0.0, 4.0)).is_none());
+		return;
+	}
+
+	#[test]
+	fn sweep_along_and_into_a_slope() {
+		// y = x, rising to +x
+		let world = CollisionWorld::new(quad(
+			(-5.0, -5.0, -5.0).into(),
+			(5.0, 5.0, -5.0).into(),
+			(5.0, 5.0, 5.0).into(),
+			(-5.0, -5.0, 5.0).into(),
+		));
+		let normal = Vector3::new(-1.0, 1.0, 0.0).normalize();
+		// 0.1 clear of it
+		let center = Point3::new(0.0, 0.0, 0.0) + normal * 0.6;
+		let sphere = Capsule { a: center, b: center, radius: 0.5 };
+
+		assert!(world.sweep_capsule(&(sphere), Vector3::new(2.0, 2.0, 0.0)).is_none());
+
+		let hit = world.sweep_capsule(&(sphere), Vector3::new(0.0, -2.0, 0.0)).unwrap();
+		assert!(close(hit.normal, normal));
+		// closing at 2 / sqrt(2) a unit of time
+		let expected = 0.1 / 2.0f32.sqrt();
+		assert!((hit.time - expected).abs() < SKIN, "{}", hit.time);
+		return;
+	}
+
+	#[test]
+	fn touching_the_capsule_isnt_overlapping() {
+		let world = CollisionWorld::new(vec![Triangle::new((0.0, 0.0, 0.0).into(), (1.0, 0.0, 0.0).into(), (0.0, 0.0, 1.0).into())]);
+		// the bottom sphere's centre is exactly a radius above it
+		assert!(world.overlap_capsule(&(Capsule::standing((0.25, 0.0, 0.25).into(), 0.5, 2.0))).is_empty());
+
+		let overlaps = world.overlap_capsule(&(Capsule::standing((0.25, -0.125, 0.25).into(), 0.5, 2.0)));
+		assert_eq!(overlaps.len(), 1);
+		assert!((overlaps[0].depth - 0.125).abs() < 1e-6);
+		assert!(close(overlaps[0].normal, Vector3::unit_y()));
+		return;
+	}
+
+	#[test]
+	fn ground_at_triangle_edges() {
+		let world = floor();
+		let up = Vector3::unit_y();
+		// on the seam between the top's two triangles
+		let hit = world.ground_below((0.0, 0.5, 0.0).into(), 0.25, 0.0, 1.0).unwrap();
+		assert!(hit.height.abs() < 1e-6);
+		assert!(close(hit.normal, up));
+
+		// right above the edge, and hanging over it
+		let hit = world.ground_below((0.5, 0.5, 0.0).into(), 0.25, 0.0, 1.0).unwrap();
+		assert!(hit.height.abs() < 1e-6);
+		assert!(close(hit.normal, up));
+		let hit = world.ground_below((0.6, 0.5, 0.0).into(), 0.25, 0.0, 1.0).unwrap();
+		assert!(hit.height.abs() < 1e-6);
+		assert!(hit.normal.x > 0.0 && hit.normal.y > 0.0);
+
+		// clear of it
+		assert!(world.ground_below((0.8, 0.5, 0.0).into(), 0.25, 0.0, 1.0).is_none());
+		return;
+	}
+}
@@ -0,0 +1,70 @@
+use cgmath::{Deg, Vector3, Rad, InnerSpace};
+use crate::{camera::{Camera, pitch_clamp}, input::Input};
+
+// detached camera for looking at the world from outside of the player.
+// the player keeps simulating (with no input) while this is active.
+pub struct DebugCamera {
+	pub input: Input,
+	pub camera: Camera,
+
+	pub speed: f32,
+	pub fast_multiplier: f32,
+	pub slow_multiplier: f32,
+}
+
+impl DebugCamera {
+	pub fn new(camera: &Camera, input: &Input) -> Self {
+		let mut camera = camera.clone();
+		camera.clear_offsets();
+		return Self {
+			input: Input::new(input.speed, input.base_dots_per_deg * 360.0),
+			camera,
+
+			speed: 4.0,
+			fast_multiplier: 5.0,
+			slow_multiplier: 0.2,
+		};
+	}
+
+	pub fn snap_to(&mut self, camera: &Camera) {
+		self.camera.position = camera.position;
+		self.camera.rot = camera.rot;
+		return;
+	}
+
+	// unlike the player, the debug camera moves where it is looking (including pitch)
+	pub fn forward_right(&self) -> (Vector3<f32>, Vector3<f32>) {
+		let (sin_yaw, cos_yaw) = Rad::from(self.camera.rot.x).0.sin_cos();
+		let (sin_pitch, cos_pitch) = Rad::from(self.camera.rot.y).0.sin_cos();
+		let forward = Vector3::new(cos_pitch * cos_yaw, sin_pitch, cos_pitch * sin_yaw).normalize();
+		let right = Vector3::new(-sin_yaw, 0.0, cos_yaw).normalize();
+
+		return (forward, right);
+	}
+
+	pub fn update(&mut self, dt: f32, sf: f32) {
+		let mut speed = self.speed;
+		// sprint/crouch keys speed up/slow down the camera
+		if self.input.sprint {
+			speed *= self.fast_multiplier;
+		}
+		if self.input.crouch {
+			speed *= self.slow_multiplier;
+		}
+
+		let (forward, right) = self.forward_right();
+		let input = &(self.input);
+		let mut position = self.camera.position.expect("debug camera has no position");
+		position += forward * (input.amount_forward - input.amount_backward) * (speed * dt);
+		position += right * (input.amount_right - input.amount_left) * (speed * dt);
+		position.y += (input.amount_up - input.amount_down) * (speed * dt);
+		self.camera.set_pos(position);
+
+		// doesn't need dt because the input is not continuous.
+		let (dx, dy) = input.mouse_moved;
+		self.camera.rot.x += Deg((dx / input.dots_per_deg) * sf);
+		self.camera.rot.y = pitch_clamp(self.camera.rot.y.0 + (-dy / input.dots_per_deg * sf));
+
+		return;
+	}
+}
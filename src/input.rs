@@ -1,6 +1,142 @@
-use winit::{event::ElementState, keyboard::KeyCode};
+use winit::{event::{ElementState, MouseButton, MouseScrollDelta}, keyboard::KeyCode};
 
-#[derive(Debug)]
+use crate::build::PieceKind;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+	Forward,
+	Backward,
+	Left,
+	Right,
+	Up,
+	Down,
+	Sprint,
+	Crouch,
+	LookLeft,
+	LookRight,
+	LookUp,
+	LookDown,
+	ToggleNoclip,
+	Fire,
+	Build(PieceKind),
+	Edit,
+	ResetEdit,
+	RotateEdit,
+	NextMaterial,
+	NextSlot,
+	Reload,
+	// hotbar slot, from 0
+	Slot(usize),
+	// pick up on press, open chests while held
+	Interact,
+	DropItem,
+	MoveItemLeft,
+	MoveItemRight,
+}
+
+// which keys drive which actions, so that several local players can share a keyboard
+#[derive(Debug, Clone)]
+pub struct Bindings {
+	keys: Vec<(KeyCode, Action)>,
+}
+
+impl Bindings {
+	pub fn none() -> Self {
+		return Self { keys: Vec::new() };
+	}
+
+	// the first local player also has the mouse, so no look keys
+	pub fn primary() -> Self {
+		return Self {
+			keys: vec![
+				(KeyCode::KeyI, Action::Forward),
+				(KeyCode::KeyJ, Action::Left),
+				(KeyCode::KeyK, Action::Backward),
+				(KeyCode::KeyL, Action::Right),
+				(KeyCode::Space, Action::Up),
+				(KeyCode::Semicolon, Action::Down),
+				(KeyCode::ShiftLeft, Action::Sprint),
+				(KeyCode::ControlLeft, Action::Crouch),
+				// was V, until the build pieces took Z X C V
+				(KeyCode::KeyN, Action::ToggleNoclip),
+				(KeyCode::KeyZ, Action::Build(PieceKind::Wall)),
+				(KeyCode::KeyX, Action::Build(PieceKind::Floor)),
+				(KeyCode::KeyC, Action::Build(PieceKind::Ramp)),
+				(KeyCode::KeyV, Action::Build(PieceKind::Cone)),
+				(KeyCode::KeyG, Action::Edit),
+				(KeyCode::KeyT, Action::ResetEdit),
+				(KeyCode::KeyR, Action::RotateEdit),
+				(KeyCode::KeyB, Action::NextMaterial),
+				(KeyCode::KeyQ, Action::NextSlot),
+				(KeyCode::KeyU, Action::Reload),
+				(KeyCode::Digit1, Action::Slot(0)),
+				(KeyCode::Digit2, Action::Slot(1)),
+				(KeyCode::Digit3, Action::Slot(2)),
+				(KeyCode::Digit4, Action::Slot(3)),
+				(KeyCode::Digit5, Action::Slot(4)),
+				(KeyCode::KeyE, Action::Interact),
+				(KeyCode::KeyY, Action::DropItem),
+				(KeyCode::Comma, Action::MoveItemLeft),
+				(KeyCode::Period, Action::MoveItemRight),
+			],
+		};
+	}
+
+	pub fn secondary() -> Self {
+		return Self {
+			keys: vec![
+				(KeyCode::ArrowUp, Action::Forward),
+				(KeyCode::ArrowLeft, Action::Left),
+				(KeyCode::ArrowDown, Action::Backward),
+				(KeyCode::ArrowRight, Action::Right),
+				(KeyCode::Enter, Action::Up),
+				(KeyCode::Backslash, Action::Down),
+				(KeyCode::ShiftRight, Action::Sprint),
+				(KeyCode::ControlRight, Action::Crouch),
+				(KeyCode::Delete, Action::LookLeft),
+				(KeyCode::PageDown, Action::LookRight),
+				(KeyCode::Home, Action::LookUp),
+				(KeyCode::End, Action::LookDown),
+				(KeyCode::Insert, Action::ToggleNoclip),
+				(KeyCode::Slash, Action::Fire),
+				(KeyCode::Digit7, Action::Build(PieceKind::Wall)),
+				(KeyCode::Digit8, Action::Build(PieceKind::Floor)),
+				(KeyCode::Digit9, Action::Build(PieceKind::Ramp)),
+				(KeyCode::Digit0, Action::Build(PieceKind::Cone)),
+				(KeyCode::Digit6, Action::Edit),
+				(KeyCode::Equal, Action::ResetEdit),
+				(KeyCode::Minus, Action::RotateEdit),
+				(KeyCode::NumpadMultiply, Action::NextMaterial),
+				(KeyCode::NumpadAdd, Action::NextSlot),
+				(KeyCode::NumpadSubtract, Action::Reload),
+				(KeyCode::Numpad1, Action::Slot(0)),
+				(KeyCode::Numpad2, Action::Slot(1)),
+				(KeyCode::Numpad3, Action::Slot(2)),
+				(KeyCode::Numpad4, Action::Slot(3)),
+				(KeyCode::Numpad5, Action::Slot(4)),
+				(KeyCode::Numpad0, Action::Interact),
+				(KeyCode::NumpadDecimal, Action::DropItem),
+				(KeyCode::Numpad7, Action::MoveItemLeft),
+				(KeyCode::Numpad9, Action::MoveItemRight),
+			],
+		};
+	}
+
+	// there's only one keyboard, so players past the second have nothing for now
+	pub fn for_local_player(index: usize) -> Self {
+		return match index {
+			0 => Self::primary(),
+			1 => Self::secondary(),
+			_ => Self::none(),
+		};
+	}
+
+	pub fn action(&self, key: KeyCode) -> Option<Action> {
+		return self.keys.iter().find(|&&(k, _)| k == key).map(|&(_, action)| action);
+	}
+}
+
+#[derive(Debug, Clone)]
 pub struct Input {
 	pub amount_left: f32,
 	pub amount_right: f32,
@@ -8,9 +144,41 @@ pub struct Input {
 	pub amount_backward: f32,
 	pub amount_up: f32,
 	pub amount_down: f32,
+	// set when up is pressed, for jumping out of the bus and opening the glider
+	pub jump: bool,
+	pub sprint: bool,
+	pub crouch: bool,
+	// whether sprint/crouch flip on each press instead of being held
+	pub sprint_toggle: bool,
+	pub crouch_toggle: bool,
+	pub amount_look_left: f32,
+	pub amount_look_right: f32,
+	pub amount_look_up: f32,
+	pub amount_look_down: f32,
+	// set on press, cleared once the player has toggled
+	pub toggle_noclip: bool,
+	// set on press, cleared once the player has picked it up
+	pub select_piece: Option<PieceKind>,
+	pub toggle_edit: bool,
+	pub reset_edit: bool,
+	pub rotate_edit: bool,
+	pub next_material: bool,
+	pub reload: bool,
+	// hotbar slots to move through, from the mouse wheel or keys
+	pub scroll_slots: i32,
+	pub select_slot: Option<usize>,
+	pub pick_up: bool,
+	pub interacting: bool,
+	pub drop_item: bool,
+	// which way to move the selected item in the hotbar
+	pub move_item: i32,
 	pub mouse_moved: (f32, f32),
+	pub aiming: bool,
+	pub firing: bool,
 	pub speed: f32,
+	// scaled from base_dots_per_deg by zoom, see Camera::sensitivity_scale
 	pub dots_per_deg: f32,
+	pub base_dots_per_deg: f32,
 }
 
 impl Input {
@@ -22,38 +190,205 @@ impl Input {
 			amount_backward: 0.0,
 			amount_up: 0.0,
 			amount_down: 0.0,
+			jump: false,
+			sprint: false,
+			crouch: false,
+			sprint_toggle: false,
+			crouch_toggle: false,
+			amount_look_left: 0.0,
+			amount_look_right: 0.0,
+			amount_look_up: 0.0,
+			amount_look_down: 0.0,
+			toggle_noclip: false,
+			select_piece: None,
+			toggle_edit: false,
+			reset_edit: false,
+			rotate_edit: false,
+			next_material: false,
+			reload: false,
+			scroll_slots: 0,
+			select_slot: None,
+			pick_up: false,
+			interacting: false,
+			drop_item: false,
+			move_item: 0,
 			mouse_moved: (0.0, 0.0),
+			aiming: false,
+			firing: false,
 			speed,
 			dots_per_deg: dots_per_360deg / 360.0,
+			base_dots_per_deg: dots_per_360deg / 360.0,
 		};
 	}
 
-	pub fn process_key(&mut self, key: KeyCode, state: ElementState) {
-		let amount = if state == ElementState::Pressed { 1.0 } else { 0.0 };
-		match key {
-			KeyCode::KeyI => {
+	pub fn process_key(&mut self, bindings: &Bindings, key: KeyCode, state: ElementState) {
+		if let Some(action) = bindings.action(key) {
+			self.process_action(action, state);
+		}
+		return;
+	}
+
+	pub fn process_action(&mut self, action: Action, state: ElementState) {
+		let pressed = state == ElementState::Pressed;
+		let amount = if pressed { 1.0 } else { 0.0 };
+		match action {
+			Action::Forward => {
 				self.amount_forward = amount;
 			}
-			KeyCode::KeyJ => {
+			Action::Left => {
 				self.amount_left = amount;
 			}
-			KeyCode::KeyK => {
+			Action::Backward => {
 				self.amount_backward = amount;
 			}
-			KeyCode::KeyL => {
+			Action::Right => {
 				self.amount_right = amount;
 			}
-			KeyCode::Space => {
+			Action::Up => {
 				self.amount_up = amount;
+				self.jump |= pressed;
 			}
-			KeyCode::Semicolon => {
+			Action::Down => {
 				self.amount_down = amount;
 			}
+			Action::Sprint => {
+				if !self.sprint_toggle {
+					self.sprint = pressed;
+				} else if pressed {
+					self.sprint = !self.sprint;
+				}
+			}
+			Action::Crouch => {
+				if !self.crouch_toggle {
+					self.crouch = pressed;
+				} else if pressed {
+					self.crouch = !self.crouch;
+				}
+			}
+			Action::LookLeft => {
+				self.amount_look_left = amount;
+			}
+			Action::LookRight => {
+				self.amount_look_right = amount;
+			}
+			Action::LookUp => {
+				self.amount_look_up = amount;
+			}
+			Action::LookDown => {
+				self.amount_look_down = amount;
+			}
+			Action::ToggleNoclip => {
+				self.toggle_noclip |= pressed;
+			}
+			Action::Fire => {
+				self.firing = pressed;
+			}
+			Action::Build(kind) => {
+				if pressed {
+					self.select_piece = Some(kind);
+				}
+			}
+			Action::Edit => {
+				self.toggle_edit |= pressed;
+			}
+			Action::ResetEdit => {
+				self.reset_edit |= pressed;
+			}
+			Action::RotateEdit => {
+				self.rotate_edit |= pressed;
+			}
+			Action::NextMaterial => {
+				self.next_material |= pressed;
+			}
+			Action::NextSlot => {
+				if pressed {
+					self.scroll_slots += 1;
+				}
+			}
+			Action::Reload => {
+				self.reload |= pressed;
+			}
+			Action::Slot(index) => {
+				if pressed {
+					self.select_slot = Some(index);
+				}
+			}
+			Action::Interact => {
+				self.pick_up |= pressed;
+				self.interacting = pressed;
+			}
+			Action::DropItem => {
+				self.drop_item |= pressed;
+			}
+			Action::MoveItemLeft => {
+				if pressed {
+					self.move_item -= 1;
+				}
+			}
+			Action::MoveItemRight => {
+				if pressed {
+					self.move_item += 1;
+				}
+			}
+		};
+		return;
+	}
+
+	pub fn process_mouse_button(&mut self, button: MouseButton, state: ElementState) {
+		match button {
+			MouseButton::Left => self.process_action(Action::Fire, state),
+			MouseButton::Right => self.aiming = state == ElementState::Pressed,
 			_ => (),
 		};
 		return;
 	}
 
+	// scrolling down moves to the next slot
+	pub fn process_scroll(&mut self, delta: MouseScrollDelta) {
+		let y = match delta {
+			MouseScrollDelta::LineDelta(_, y) => y,
+			MouseScrollDelta::PixelDelta(position) => position.y as f32,
+		};
+		if y != 0.0 {
+			self.scroll_slots -= y.signum() as i32;
+		}
+		return;
+	}
+
+	// forget held keys, e.g. when input is redirected elsewhere
+	pub fn release_all(&mut self) {
+		self.amount_left = 0.0;
+		self.amount_right = 0.0;
+		self.amount_forward = 0.0;
+		self.amount_backward = 0.0;
+		self.amount_up = 0.0;
+		self.amount_down = 0.0;
+		self.jump = false;
+		self.sprint = false;
+		self.crouch = false;
+		self.amount_look_left = 0.0;
+		self.amount_look_right = 0.0;
+		self.amount_look_up = 0.0;
+		self.amount_look_down = 0.0;
+		self.toggle_noclip = false;
+		self.select_piece = None;
+		self.toggle_edit = false;
+		self.reset_edit = false;
+		self.rotate_edit = false;
+		self.next_material = false;
+		self.reload = false;
+		self.scroll_slots = 0;
+		self.select_slot = None;
+		self.pick_up = false;
+		self.interacting = false;
+		self.drop_item = false;
+		self.move_item = 0;
+		self.aiming = false;
+		self.firing = false;
+		self.mouse_moved = (0.0, 0.0);
+		return;
+	}
+
 	pub fn set_mouse_motion(&mut self, (dx, dy): (f64, f64)) {
 		self.mouse_moved = (dx as f32, dy as f32);
 		return;
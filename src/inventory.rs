@@ -0,0 +1,468 @@
+use crate::{
+	build::{BuildMaterial, Resources, MAX_RESOURCES},
+	weapon::{Weapon, WeaponKind},
+};
+
+pub const HOTBAR_SLOTS: usize = 5;
+// per kind of ammo
+pub const MAX_AMMO: u32 = 999;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AmmoKind {
+	Light,
+	Medium,
+	Heavy,
+	Shells,
+	Rockets,
+}
+
+impl AmmoKind {
+	pub const ALL: [Self; 5] = [Self::Light, Self::Medium, Self::Heavy, Self::Shells, Self::Rockets];
+
+	pub fn index(self) -> usize {
+		return self as usize;
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsumableKind {
+	Bandage,
+	Medkit,
+	SmallShield,
+	ShieldPotion,
+}
+
+pub struct ConsumableStats {
+	// seconds of channelling before it does anything
+	pub use_time: f32,
+	pub health: f32,
+	pub shield: f32,
+	// neither goes past these, e.g. bandages only heal up to 75
+	pub health_cap: f32,
+	pub shield_cap: f32,
+	// fraction of the usual speed while using it
+	pub move_speed: f32,
+}
+
+impl ConsumableKind {
+	pub fn stats(self) -> ConsumableStats {
+		return match self {
+			Self::Bandage => ConsumableStats { use_time: 3.5, health: 15.0, shield: 0.0, health_cap: 75.0, shield_cap: 0.0, move_speed: 0.5 },
+			Self::Medkit => ConsumableStats { use_time: 10.0, health: 100.0, shield: 0.0, health_cap: 100.0, shield_cap: 0.0, move_speed: 0.3 },
+			Self::SmallShield => ConsumableStats { use_time: 2.0, health: 0.0, shield: 25.0, health_cap: 0.0, shield_cap: 50.0, move_speed: 0.5 },
+			Self::ShieldPotion => ConsumableStats { use_time: 5.0, health: 0.0, shield: 50.0, health_cap: 0.0, shield_cap: 100.0, move_speed: 0.3 },
+		};
+	}
+
+	pub fn max_stack(self) -> u32 {
+		return match self {
+			Self::Bandage => 15,
+			Self::Medkit => 3,
+			Self::SmallShield => 6,
+			Self::ShieldPotion => 3,
+		};
+	}
+}
+
+impl WeaponKind {
+	// None for things that are thrown, which are their own ammo
+	pub fn ammo(self) -> Option<AmmoKind> {
+		return match self {
+			Self::Smg | Self::Pistol => Some(AmmoKind::Light),
+			Self::AssaultRifle => Some(AmmoKind::Medium),
+			Self::Sniper => Some(AmmoKind::Heavy),
+			Self::Shotgun => Some(AmmoKind::Shells),
+			Self::RocketLauncher => Some(AmmoKind::Rockets),
+			Self::Grenade => None,
+		};
+	}
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Item {
+	Weapon(Weapon),
+	Consumable(ConsumableKind, u32),
+	Ammo(AmmoKind, u32),
+	Material(BuildMaterial, u32),
+}
+
+impl Item {
+	// weapons and consumables go in the hotbar, ammo and materials don't take up a slot
+	pub fn takes_slot(&self) -> bool {
+		return matches!(self, Self::Weapon(_) | Self::Consumable(..));
+	}
+
+	fn stacks_with(&self, other: &Item) -> bool {
+		return match (self, other) {
+			(Self::Consumable(a, _), Self::Consumable(b, _)) => a == b,
+			(Self::Weapon(a), Self::Weapon(b)) => a.kind == b.kind && a.kind.ammo().is_none(),
+			_ => false,
+		};
+	}
+
+	// moves as much of `other` onto this stack as fits, returning the rest
+	fn stack(&mut self, other: Item) -> Option<Item> {
+		if !self.stacks_with(&(other)) {
+			return Some(other);
+		}
+		let (count, limit, incoming) = match (self, other) {
+			(Self::Consumable(kind, count), Self::Consumable(_, incoming)) => (count, kind.max_stack(), incoming),
+			(Self::Weapon(weapon), Self::Weapon(incoming)) => (&mut(weapon.ammo), weapon.kind.stats().magazine, incoming.ammo),
+			_ => unreachable!(),
+		};
+		let taken = incoming.min(limit.saturating_sub(*count));
+		*count += taken;
+		return if taken < incoming { Some(other.with_count(incoming - taken)) } else { None };
+	}
+
+	// a reload only counts down while the weapon's out, so putting it away cancels it
+	fn put_away(&mut self) {
+		if let Self::Weapon(weapon) = self {
+			weapon.reloading = None;
+		}
+		return;
+	}
+
+	fn with_count(mut self, to: u32) -> Self {
+		match &mut(self) {
+			Self::Weapon(weapon) => weapon.ammo = to,
+			Self::Consumable(_, count) | Self::Ammo(_, count) | Self::Material(_, count) => *count = to,
+		};
+		return self;
+	}
+}
+
+pub struct Inventory {
+	pub slots: [Option<Item>; HOTBAR_SLOTS],
+	// index into slots, or None for the pickaxe
+	pub selected: Option<usize>,
+	pub ammo: [u32; AmmoKind::ALL.len()],
+	pub resources: Resources,
+}
+
+impl Inventory {
+	pub fn new() -> Self {
+		return Self {
+			slots: [None; HOTBAR_SLOTS],
+			selected: None,
+			ammo: [0; AmmoKind::ALL.len()],
+			resources: Resources::default(),
+		};
+	}
+
+	// the selected weapon, and the ammo it reloads from
+	pub fn equipped_weapon(&mut self) -> Option<(&mut Weapon, Option<&mut u32>)> {
+		let Some(Item::Weapon(weapon)) = self.slots[self.selected?].as_mut() else {
+			return None;
+		};
+		let reserve = weapon.kind.ammo().map(|ammo| &mut(self.ammo[ammo.index()]));
+		return Some((weapon, reserve));
+	}
+
+	pub fn reload(&mut self) {
+		if let Some((weapon, Some(reserve))) = self.equipped_weapon() {
+			weapon.reload(*reserve);
+		}
+		return;
+	}
+
+	// for when something else is got out without changing the selected slot, e.g. a build piece
+	pub fn put_away_selected(&mut self) {
+		if let Some(item) = self.selected.and_then(|index| self.slots[index].as_mut()) {
+			item.put_away();
+		}
+		return;
+	}
+
+	fn set_selected(&mut self, to: Option<usize>) {
+		if to != self.selected {
+			self.put_away_selected();
+		}
+		self.selected = to;
+		return;
+	}
+
+	// puts away as much of the item as there's room for, returning the rest
+	pub fn add(&mut self, item: Item) -> Option<Item> {
+		let (taken, incoming) = match item {
+			Item::Ammo(kind, incoming) => {
+				let reserve = &mut(self.ammo[kind.index()]);
+				let taken = incoming.min(MAX_AMMO - *reserve);
+				*reserve += taken;
+				(taken, incoming)
+			}
+			Item::Material(material, incoming) => {
+				let taken = incoming.min(MAX_RESOURCES - self.resources.get(material));
+				self.resources.add(material, taken);
+				(taken, incoming)
+			}
+			Item::Weapon(_) | Item::Consumable(..) => return self.add_to_slots(item),
+		};
+		return if taken < incoming { Some(item.with_count(incoming - taken)) } else { None };
+	}
+
+	// onto what's already carried first, then into an empty slot
+	fn add_to_slots(&mut self, mut item: Item) -> Option<Item> {
+		for slot in self.slots.iter_mut().flatten() {
+			item = slot.stack(item)?;
+		}
+		let Some(slot) = self.slots.iter_mut().find(|slot| slot.is_none()) else {
+			return Some(item);
+		};
+		*slot = Some(item);
+		return None;
+	}
+
+	// like add, but with a full hotbar the selected item is swapped out for it
+	pub fn pick_up(&mut self, item: Item) -> Option<Item> {
+		let stacked = self.slots.iter().flatten().any(|slot| slot.stacks_with(&(item)));
+		let rest = self.add(item)?;
+		if stacked || !rest.takes_slot() {
+			return Some(rest);
+		}
+		let Some(index) = self.selected else {
+			return Some(rest);
+		};
+		let mut swapped = self.slots[index].replace(rest)?;
+		swapped.put_away();
+		return Some(swapped);
+	}
+
+	pub fn drop_selected(&mut self) -> Option<Item> {
+		let mut item = self.slots[self.selected?].take()?;
+		item.put_away();
+		self.selected = None;
+		return Some(item);
+	}
+
+	// thrown things are gone once the last one is
+	pub fn remove_spent(&mut self) {
+		for (index, slot) in self.slots.iter_mut().enumerate() {
+			if let Some(Item::Weapon(weapon)) = slot {
+				if weapon.kind.ammo().is_none() && weapon.ammo == 0 {
+					*slot = None;
+					if self.selected == Some(index) {
+						self.selected = None;
+					}
+				}
+			}
+		}
+		return;
+	}
+
+	// empty slots can't be selected
+	pub fn select(&mut self, index: usize) {
+		if self.slots.get(index).is_some_and(|slot| slot.is_some()) {
+			self.set_selected(Some(index));
+		}
+		return;
+	}
+
+	// through the pickaxe and every slot with something in it, wrapping around
+	pub fn scroll(&mut self, steps: i32) {
+		let mut choices = vec![None];
+		choices.extend((0..HOTBAR_SLOTS).filter(|&index| self.slots[index].is_some()).map(Some));
+		let current = choices.iter().position(|&choice| choice == self.selected).unwrap_or(0);
+		self.set_selected(choices[(current as i32 + steps).rem_euclid(choices.len() as i32) as usize]);
+		return;
+	}
+
+	// swaps the selected item with its neighbour, and keeps it selected
+	pub fn move_selected(&mut self, steps: i32) {
+		let Some(index) = self.selected else {
+			return;
+		};
+		let target = (index as i32 + steps).clamp(0, HOTBAR_SLOTS as i32 - 1) as usize;
+		self.slots.swap(index, target);
+		self.selected = Some(target);
+		return;
+	}
+
+	// everything, leaving the inventory empty
+	pub fn take_all(&mut self) -> Vec<Item> {
+		let mut items = self.slots.iter_mut().filter_map(Option::take).collect::<Vec<_>>();
+		for item in &mut(items) {
+			item.put_away();
+		}
+		for kind in AmmoKind::ALL {
+			let amount = std::mem::take(&mut(self.ammo[kind.index()]));
+			if amount > 0 {
+				items.push(Item::Ammo(kind, amount));
+			}
+		}
+		let resources = std::mem::take(&mut(self.resources));
+		for material in BuildMaterial::ALL {
+			let amount = resources.get(material);
+			if amount > 0 {
+				items.push(Item::Material(material, amount));
+			}
+		}
+		self.selected = None;
+		return items;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::loot::Rarity;
+
+	fn weapon(kind: WeaponKind) -> Item {
+		return Item::Weapon(Weapon::new(kind, Rarity::Common));
+	}
+
+	fn slot_count(inventory: &Inventory, index: usize) -> Option<u32> {
+		return match inventory.slots[index]? {
+			Item::Weapon(weapon) => Some(weapon.ammo),
+			Item::Consumable(_, count) | Item::Ammo(_, count) | Item::Material(_, count) => Some(count),
+		};
+	}
+
+	#[test]
+	fn consumables_and_grenades_stack() {
+		let mut inventory = Inventory::new();
+		assert!(inventory.add(Item::Consumable(ConsumableKind::Bandage, 10)).is_none());
+		// only 5 more fit on the first stack, the rest starts another
+		assert!(inventory.add(Item::Consumable(ConsumableKind::Bandage, 8)).is_none());
+		assert_eq!(slot_count(&(inventory), 0), Some(15));
+		assert_eq!(slot_count(&(inventory), 1), Some(3));
+
+		assert!(inventory.add(weapon(WeaponKind::Grenade).with_count(4)).is_none());
+		assert!(inventory.add(weapon(WeaponKind::Grenade).with_count(1)).is_none());
+		assert_eq!(slot_count(&(inventory), 2), Some(5));
+		// guns never stack, even with the same kind
+		assert!(inventory.add(weapon(WeaponKind::Smg)).is_none());
+		assert!(inventory.add(weapon(WeaponKind::Smg)).is_none());
+		assert!(inventory.slots.iter().all(Option::is_some));
+
+		// with nowhere else to go, whatever didn't fit comes back
+		let rest = inventory.add(Item::Consumable(ConsumableKind::Medkit, 2));
+		assert!(matches!(rest, Some(Item::Consumable(ConsumableKind::Medkit, 2))));
+		let rest = inventory.add(weapon(WeaponKind::Grenade).with_count(3));
+		assert!(matches!(rest, Some(Item::Weapon(weapon)) if weapon.ammo == 2));
+		assert_eq!(slot_count(&(inventory), 2), Some(6));
+		return;
+	}
+
+	#[test]
+	fn ammo_and_materials_are_capped() {
+		let mut inventory = Inventory::new();
+		assert!(inventory.add(Item::Ammo(AmmoKind::Shells, 990)).is_none());
+		let rest = inventory.add(Item::Ammo(AmmoKind::Shells, 20));
+		assert!(matches!(rest, Some(Item::Ammo(AmmoKind::Shells, 11))));
+		assert_eq!(inventory.ammo[AmmoKind::Shells.index()], MAX_AMMO);
+		assert_eq!(inventory.ammo[AmmoKind::Light.index()], 0);
+
+		assert!(inventory.add(Item::Material(BuildMaterial::Wood, 500)).is_none());
+		let rest = inventory.add(Item::Material(BuildMaterial::Wood, 600));
+		assert!(matches!(rest, Some(Item::Material(BuildMaterial::Wood, 101))));
+		assert_eq!(inventory.resources.get(BuildMaterial::Wood), MAX_RESOURCES);
+		// neither takes up a slot
+		assert!(inventory.slots.iter().all(Option::is_none));
+		return;
+	}
+
+	#[test]
+	fn picking_up_with_a_full_hotbar_swaps() {
+		let mut inventory = Inventory::new();
+		for _ in 0..HOTBAR_SLOTS {
+			assert!(inventory.pick_up(weapon(WeaponKind::Pistol)).is_none());
+		}
+		// holding the pickaxe, nothing gets swapped out
+		let rest = inventory.pick_up(weapon(WeaponKind::Shotgun));
+		assert!(matches!(rest, Some(Item::Weapon(weapon)) if weapon.kind == WeaponKind::Shotgun));
+
+		inventory.select(3);
+		let swapped = inventory.pick_up(weapon(WeaponKind::Shotgun));
+		assert!(matches!(swapped, Some(Item::Weapon(weapon)) if weapon.kind == WeaponKind::Pistol));
+		assert!(matches!(inventory.slots[3], Some(Item::Weapon(weapon)) if weapon.kind == WeaponKind::Shotgun));
+		assert_eq!(inventory.selected, Some(3));
+		// ammo still goes in even when the hotbar's full
+		assert!(inventory.pick_up(Item::Ammo(AmmoKind::Light, 30)).is_none());
+		return;
+	}
+
+	#[test]
+	fn scrolling_wraps_through_the_pickaxe() {
+		let mut inventory = Inventory::new();
+		inventory.slots[1] = Some(weapon(WeaponKind::Smg));
+		inventory.slots[3] = Some(Item::Consumable(ConsumableKind::Bandage, 5));
+		// empty slots can't be picked
+		inventory.select(0);
+		assert_eq!(inventory.selected, None);
+
+		inventory.scroll(1);
+		assert_eq!(inventory.selected, Some(1));
+		inventory.scroll(1);
+		assert_eq!(inventory.selected, Some(3));
+		inventory.scroll(1);
+		assert_eq!(inventory.selected, None);
+		inventory.scroll(-1);
+		assert_eq!(inventory.selected, Some(3));
+		inventory.scroll(5);
+		assert_eq!(inventory.selected, Some(1));
+		return;
+	}
+
+	#[test]
+	fn moving_the_selected_item() {
+		let mut inventory = Inventory::new();
+		inventory.slots[0] = Some(weapon(WeaponKind::Smg));
+		inventory.slots[1] = Some(weapon(WeaponKind::Shotgun));
+		inventory.select(0);
+		inventory.move_selected(1);
+		assert_eq!(inventory.selected, Some(1));
+		assert!(matches!(inventory.slots[0], Some(Item::Weapon(weapon)) if weapon.kind == WeaponKind::Shotgun));
+		assert!(matches!(inventory.slots[1], Some(Item::Weapon(weapon)) if weapon.kind == WeaponKind::Smg));
+
+		// onto an empty slot, and no further than the end
+		inventory.move_selected(10);
+		assert_eq!(inventory.selected, Some(HOTBAR_SLOTS - 1));
+		assert!(inventory.slots[1].is_none());
+		assert!(inventory.slots[HOTBAR_SLOTS - 1].is_some());
+		// it swaps with whatever's there rather than shifting everything along
+		inventory.move_selected(-10);
+		assert_eq!(inventory.selected, Some(0));
+		assert!(matches!(inventory.slots[HOTBAR_SLOTS - 1], Some(Item::Weapon(weapon)) if weapon.kind == WeaponKind::Shotgun));
+
+		// nothing to move with the pickaxe out
+		inventory.scroll(-1);
+		inventory.move_selected(1);
+		assert_eq!(inventory.selected, None);
+		assert!(matches!(inventory.slots[0], Some(Item::Weapon(weapon)) if weapon.kind == WeaponKind::Smg));
+		return;
+	}
+
+	#[test]
+	fn switching_away_cancels_a_reload() {
+		let reloading = |inventory: &Inventory, index: usize| matches!(inventory.slots[index], Some(Item::Weapon(weapon)) if weapon.reloading.is_some());
+		let mut inventory = Inventory::new();
+		inventory.add(weapon(WeaponKind::Smg).with_count(10));
+		inventory.add(weapon(WeaponKind::Pistol));
+		inventory.add(Item::Ammo(AmmoKind::Light, 100));
+
+		inventory.select(0);
+		inventory.reload();
+		assert!(reloading(&(inventory), 0));
+		// picking the same slot again leaves it be
+		inventory.select(0);
+		assert!(reloading(&(inventory), 0));
+		inventory.select(1);
+		assert!(!reloading(&(inventory), 0));
+
+		inventory.select(0);
+		inventory.reload();
+		inventory.scroll(-1);
+		assert!(!reloading(&(inventory), 0));
+
+		inventory.select(0);
+		inventory.reload();
+		inventory.put_away_selected();
+		assert!(!reloading(&(inventory), 0));
+
+		inventory.reload();
+		let dropped = inventory.drop_selected();
+		assert!(matches!(dropped, Some(Item::Weapon(weapon)) if weapon.reloading.is_none()));
+		return;
+	}
+}
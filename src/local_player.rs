@@ -0,0 +1,260 @@
+use cgmath::Deg;
+use winit::dpi::PhysicalSize;
+
+use crate::{
+	build::{Placement, EditPattern, MAX_PIECE_VERTICES},
+	camera::*,
+	camera_effects::CameraEffects,
+	camera_path::*,
+	debug_camera::DebugCamera,
+	input::{Input, Bindings},
+	obj::Vertex,
+	player::{Player, Ground},
+	settings::Settings,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Viewport {
+	pub x: u32,
+	pub y: u32,
+	pub width: u32,
+	pub height: u32,
+}
+
+impl Viewport {
+	pub fn full(size: PhysicalSize<u32>) -> Self {
+		return Self { x: 0, y: 0, width: size.width, height: size.height };
+	}
+
+	// two players are stacked on top of each other, more go into a grid
+	pub fn split(size: PhysicalSize<u32>, count: usize, index: usize) -> Self {
+		assert!(index < count);
+		let (cols, rows) = match count {
+			1 => (1, 1),
+			2 => (1, 2),
+			_ => {
+				let cols = (count as f32).sqrt().ceil() as u32;
+				(cols, (count as u32).div_ceil(cols))
+			}
+		};
+		let (col, row) = (index as u32 % cols, index as u32 / cols);
+		let width = size.width / cols;
+		let height = size.height / rows;
+		return Self { x: col * width, y: row * height, width: width.max(1), height: height.max(1) };
+	}
+
+	pub fn size(&self) -> PhysicalSize<u32> {
+		return PhysicalSize::new(self.width, self.height);
+	}
+}
+
+// what would be placed right now (or the tiles cut out by an edit), and whether it's allowed
+pub type Ghost = (Placement, EditPattern, bool);
+
+// who's sitting where: everything about a local player that isn't made from the device
+pub struct Seat {
+	pub input: Input,
+	pub bindings: Bindings,
+	// index into State::players
+	pub player: usize,
+	pub viewport: Viewport,
+}
+
+// degrees per second when looking around with keys instead of the mouse
+const KEY_LOOK_SPEED: f32 = 180.0;
+
+// everything one person sitting in front of the screen needs:
+// their own input, view of the world and part of the surface.
+pub struct LocalPlayer {
+	pub input: Input,
+	pub bindings: Bindings,
+	// index into State::players
+	pub player: usize,
+
+	pub camera: Camera,
+	pub camera_effects: CameraEffects,
+	camera_uniform: CameraUniform,
+	camera_bind_group: wgpu::BindGroup,
+	pub viewport: Viewport,
+
+	pub ghost: Option<Ghost>,
+	ghost_buffer: wgpu::Buffer,
+	ghost_vertices: u32,
+
+	pub debug_camera: Option<DebugCamera>,
+	camera_recorder: Option<CameraRecorder>,
+	camera_playback: Option<CameraPlayback>,
+}
+
+impl LocalPlayer {
+	pub fn new(device: &wgpu::Device, layout: &wgpu::BindGroupLayout, settings: &Settings, seat: Seat, player_state: &Player) -> Self {
+		let Seat { input, bindings, player, viewport } = seat;
+		let mut camera = Camera::new(viewport.size(), settings);
+		camera.set_pos(player_state.adapt_pos_for_camera());
+
+		let camera_uniform = CameraUniform::new(device);
+		let camera_bind_group = device.create_bind_group(&(wgpu::BindGroupDescriptor {
+			layout,
+			entries: &[
+				wgpu::BindGroupEntry {
+					binding: 0,
+					resource: camera_uniform.as_entire_binding(),
+				}
+			],
+			label: Some("camera_bind_group"),
+		}));
+		let ghost_buffer = device.create_buffer(&(wgpu::BufferDescriptor {
+			label: Some("ghost_buffer"),
+			usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+			size: (std::mem::size_of::<Vertex>() * MAX_PIECE_VERTICES) as u64,
+			mapped_at_creation: false,
+		}));
+
+		return Self {
+			input,
+			bindings,
+			player,
+
+			camera,
+			camera_effects: CameraEffects::new(settings),
+			camera_uniform,
+			camera_bind_group,
+			viewport,
+
+			ghost: None,
+			ghost_buffer,
+			ghost_vertices: 0,
+
+			debug_camera: None,
+			camera_recorder: None,
+			camera_playback: None,
+		};
+	}
+
+	pub fn reconfigure(&mut self, viewport: Viewport) {
+		self.viewport = viewport;
+		self.camera.reconfigure(viewport.size());
+		if let Some(debug_camera) = &mut(self.debug_camera) {
+			debug_camera.camera.reconfigure(viewport.size());
+		}
+		if let Some(playback) = &mut(self.camera_playback) {
+			playback.camera.reconfigure(viewport.size());
+		}
+		return;
+	}
+
+	// returns how fast the player landed, if they did
+	pub fn update<G: Ground>(&mut self, player: &mut Player, ground: &G, dt: f32, sf: f32) -> Option<f32> {
+		let input = &mut(self.input);
+		self.camera.update_zoom(input, dt);
+		input.dots_per_deg = input.base_dots_per_deg / self.camera.sensitivity_scale();
+
+		let landed = player.apply_input(input, ground, dt, sf);
+		if let Some(impact_speed) = landed {
+			self.camera_effects.land(impact_speed);
+		}
+		self.camera.update_rot(input, sf);
+
+		// keys are continuous, unlike the mouse
+		let look_x = Deg((input.amount_look_right - input.amount_look_left) * KEY_LOOK_SPEED * dt);
+		let look_y = (input.amount_look_up - input.amount_look_down) * KEY_LOOK_SPEED * dt;
+		player.rot_x += look_x;
+		self.camera.rot.x += look_x;
+		self.camera.rot.y = pitch_clamp(self.camera.rot.y.0 + look_y);
+
+		self.camera.set_pos(player.adapt_pos_for_camera());
+		self.camera_effects.update(dt);
+		self.camera_effects.apply(&mut(self.camera));
+
+		if let Some(debug_camera) = &mut(self.debug_camera) {
+			debug_camera.update(dt, sf);
+		}
+
+		if let Some(playback) = &mut(self.camera_playback) {
+			playback.update(dt);
+			if playback.is_finished() {
+				self.camera_playback = None;
+			}
+		}
+		if let Some(recorder) = &mut(self.camera_recorder) {
+			let camera = match &(self.debug_camera) {
+				Some(debug_camera) => &(debug_camera.camera),
+				None => &(self.camera),
+			};
+			recorder.record(dt, camera);
+		}
+		return landed;
+	}
+
+	// the camera that is actually being rendered from
+	pub fn view_camera(&self) -> &Camera {
+		if let Some(playback) = &(self.camera_playback) {
+			return &(playback.camera);
+		}
+		if let Some(debug_camera) = &(self.debug_camera) {
+			return &(debug_camera.camera);
+		}
+		return &(self.camera);
+	}
+
+	pub fn write_camera(&self, queue: &wgpu::Queue, camera: &Camera) {
+		return self.camera_uniform.set_view_projection_matrix(queue, camera);
+	}
+	pub fn camera_bind_group(&self) -> &wgpu::BindGroup {
+		return &(self.camera_bind_group);
+	}
+
+	pub fn write_ghost(&mut self, queue: &wgpu::Queue) {
+		let vertices = self.ghost.map(|(placement, edit, _)| placement.vertices(edit)).unwrap_or_default();
+		queue.write_buffer(&(self.ghost_buffer), 0, bytemuck::cast_slice(&(vertices)));
+		self.ghost_vertices = vertices.len() as u32;
+		return;
+	}
+	// the ghost's vertices and whether it's valid, if there is one
+	pub fn ghost(&self) -> Option<(&wgpu::Buffer, u32, bool)> {
+		return self.ghost.map(|(_, _, valid)| (&(self.ghost_buffer), self.ghost_vertices, valid));
+	}
+
+	pub fn toggle_camera_recording<T: AsRef<std::path::Path>>(&mut self, file_name: T) -> Result<(), &'static str> {
+		match self.camera_recorder.take() {
+			Some(recorder) => recorder.path.save(file_name)?,
+			None => self.camera_recorder = Some(CameraRecorder::new(0.25)),
+		};
+		return Ok(());
+	}
+	pub fn toggle_camera_playback<T: AsRef<std::path::Path>>(&mut self, file_name: T) -> Result<(), &'static str> {
+		if self.camera_playback.take().is_none() {
+			let path = CameraPath::load(file_name)?;
+			self.camera_playback = Some(CameraPlayback::new(path, &(self.camera)));
+		}
+		return Ok(());
+	}
+	pub fn scale_camera_playback_speed(&mut self, factor: f32) {
+		if let Some(playback) = &mut(self.camera_playback) {
+			playback.speed *= factor;
+		}
+		return;
+	}
+
+	pub fn toggle_debug_camera(&mut self) {
+		if self.debug_camera.take().is_none() {
+			self.input.release_all();
+			self.debug_camera = Some(DebugCamera::new(&(self.camera), &(self.input)));
+		}
+		return;
+	}
+	pub fn snap_debug_camera(&mut self) {
+		if let Some(debug_camera) = &mut(self.debug_camera) {
+			debug_camera.snap_to(&(self.camera));
+		}
+		return;
+	}
+
+	// whichever input is currently being controlled
+	pub fn active_input(&mut self) -> &mut Input {
+		return match &mut(self.debug_camera) {
+			Some(debug_camera) => &mut(debug_camera.input),
+			None => &mut(self.input),
+		};
+	}
+}
@@ -0,0 +1,279 @@
+use std::path::Path;
+
+use crate::{
+	build::BuildMaterial,
+	inventory::{Item, AmmoKind, ConsumableKind},
+	rng::Rng,
+	weapon::{Weapon, WeaponKind},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rarity {
+	Common,
+	Uncommon,
+	Rare,
+	Epic,
+	Legendary,
+}
+
+impl Rarity {
+	pub const ALL: [Self; 5] = [Self::Common, Self::Uncommon, Self::Rare, Self::Epic, Self::Legendary];
+
+	pub fn index(self) -> usize {
+		return self as usize;
+	}
+
+	pub fn damage_scale(self) -> f32 {
+		return 1.0 + 0.05 * self.index() as f32;
+	}
+}
+
+#[derive(Debug, Clone, Copy)]
+struct LootEntry {
+	rarity: Rarity,
+	weight: f32,
+	item: Item,
+}
+
+#[derive(Debug, Clone)]
+pub struct LootTable {
+	// how likely a spawn is to give anything at all
+	chance: f32,
+	// how many things it gives when it does
+	rolls: u32,
+	rarity_weights: [f32; Rarity::ALL.len()],
+	entries: Vec<LootEntry>,
+}
+
+// one of `choices`, with odds in proportion to its weight
+fn pick<T: Copy>(rng: &mut Rng, choices: &[(T, f32)]) -> Option<T> {
+	let total = choices.iter().map(|&(_, weight)| weight).sum::<f32>();
+	if total <= 0.0 {
+		return None;
+	}
+	let mut left = rng.range(0.0, total);
+	for &(choice, weight) in choices {
+		if left < weight {
+			return Some(choice);
+		}
+		left -= weight;
+	}
+	// rounding
+	return choices.last().map(|&(choice, _)| choice);
+}
+
+impl LootTable {
+	fn new() -> Self {
+		return Self { chance: 1.0, rolls: 1, rarity_weights: [0.0; Rarity::ALL.len()], entries: Vec::new() };
+	}
+
+	// a rarity first, then something of that rarity
+	pub fn roll(&self, rng: &mut Rng) -> Vec<Item> {
+		let mut items = Vec::new();
+		if rng.next_f32() >= self.chance {
+			return items;
+		}
+		// rarities with nothing in them can't come up
+		let rarities = Rarity::ALL
+			.into_iter()
+			.filter(|&rarity| self.entries.iter().any(|entry| entry.rarity == rarity))
+			.map(|rarity| (rarity, self.rarity_weights[rarity.index()]))
+			.collect::<Vec<_>>();
+		for _ in 0..self.rolls {
+			let Some(rarity) = pick(rng, &(rarities)) else {
+				break;
+			};
+			let entries = self.entries
+				.iter()
+				.filter(|entry| entry.rarity == rarity)
+				.map(|entry| (entry.item, entry.weight))
+				.collect::<Vec<_>>();
+			let Some(item) = pick(rng, &(entries)) else {
+				break;
+			};
+			items.push(item);
+			// weapons come with something to shoot
+			if let Item::Weapon(weapon) = item {
+				if let Some(ammo) = weapon.kind.ammo() {
+					items.push(Item::Ammo(ammo, weapon.kind.stats().magazine.max(4)));
+				}
+			}
+		}
+		return items;
+	}
+}
+
+pub struct LootTables {
+	pub floor: LootTable,
+	pub chest: LootTable,
+}
+
+fn parse_rarity(name: &str) -> Result<Rarity, &'static str> {
+	return match name {
+		"common" => Ok(Rarity::Common),
+		"uncommon" => Ok(Rarity::Uncommon),
+		"rare" => Ok(Rarity::Rare),
+		"epic" => Ok(Rarity::Epic),
+		"legendary" => Ok(Rarity::Legendary),
+		_ => Err("unknown rarity in loot tables"),
+	};
+}
+
+fn parse_count(word: Option<&&str>) -> Result<u32, &'static str> {
+	return word.ok_or("loot item needs a count")?.parse().map_err(|_| "invalid count in loot tables");
+}
+
+fn parse_item(words: &[&str], rarity: Rarity) -> Result<Item, &'static str> {
+	return match words {
+		["ammo", kind, count @ ..] => Ok(Item::Ammo(match *kind {
+			"light" => AmmoKind::Light,
+			"medium" => AmmoKind::Medium,
+			"heavy" => AmmoKind::Heavy,
+			"shells" => AmmoKind::Shells,
+			"rockets" => AmmoKind::Rockets,
+			_ => return Err("unknown ammo in loot tables"),
+		}, parse_count(count.first())?)),
+		["material", material, count @ ..] => Ok(Item::Material(match *material {
+			"wood" => BuildMaterial::Wood,
+			"brick" => BuildMaterial::Brick,
+			"metal" => BuildMaterial::Metal,
+			_ => return Err("unknown material in loot tables"),
+		}, parse_count(count.first())?)),
+		["consumable", kind, count @ ..] => Ok(Item::Consumable(match *kind {
+			"bandage" => ConsumableKind::Bandage,
+			"medkit" => ConsumableKind::Medkit,
+			"small_shield" => ConsumableKind::SmallShield,
+			"shield_potion" => ConsumableKind::ShieldPotion,
+			_ => return Err("unknown consumable in loot tables"),
+		}, parse_count(count.first())?)),
+		[kind, count @ ..] => {
+			let kind = match *kind {
+				"assault_rifle" => WeaponKind::AssaultRifle,
+				"smg" => WeaponKind::Smg,
+				"shotgun" => WeaponKind::Shotgun,
+				"pistol" => WeaponKind::Pistol,
+				"sniper" => WeaponKind::Sniper,
+				"rocket_launcher" => WeaponKind::RocketLauncher,
+				"grenade" => WeaponKind::Grenade,
+				_ => return Err("unknown item in loot tables"),
+			};
+			let mut weapon = Weapon::new(kind, rarity);
+			// thrown things are their own ammo, so say how many
+			if kind.ammo().is_none() {
+				weapon.ammo = parse_count(count.first())?;
+			}
+			Ok(Item::Weapon(weapon))
+		}
+		[] => Err("loot item is missing"),
+	};
+}
+
+impl LootTables {
+	// one directive per line, see loot.txt
+	pub fn load<T: AsRef<Path>>(file_name: T) -> Result<Self, &'static str> {
+		let text = std::fs::read_to_string(file_name).map_err(|_| "failed to read loot tables")?;
+		return Self::parse(&(text));
+	}
+
+	fn parse(text: &str) -> Result<Self, &'static str> {
+		let mut tables = Self { floor: LootTable::new(), chest: LootTable::new() };
+		for line in text.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')) {
+			let words = line.split_whitespace().collect::<Vec<&str>>();
+			let (directive, table, rest) = match words[..] {
+				[directive, table, ref rest @ ..] => (directive, table, rest),
+				_ => return Err("loot table line needs a directive and a table"),
+			};
+			let table = match table {
+				"floor" => &mut(tables.floor),
+				"chest" => &mut(tables.chest),
+				_ => return Err("unknown loot table"),
+			};
+			let number = |word: Option<&&str>| -> Result<f32, &'static str> {
+				return word.ok_or("missing number in loot tables")?.parse().map_err(|_| "invalid number in loot tables");
+			};
+			// a weight of zero would just be a line that never rolls
+			let weight = |word: Option<&&str>| -> Result<f32, &'static str> {
+				let weight = number(word)?;
+				if weight > 0.0 {
+					return Ok(weight);
+				}
+				return Err("loot weights must be positive");
+			};
+			match directive {
+				"chance" => {
+					let chance = number(rest.first())?;
+					if !(0.0..=1.0).contains(&(chance)) {
+						return Err("loot chance must be between 0 and 1");
+					}
+					table.chance = chance;
+				}
+				"rolls" => table.rolls = parse_count(rest.first())?,
+				"rarity" => {
+					let rarity = parse_rarity(rest.first().ok_or("missing rarity in loot tables")?)?;
+					table.rarity_weights[rarity.index()] = weight(rest.get(1))?;
+				}
+				"item" => {
+					let rarity = parse_rarity(rest.first().ok_or("missing rarity in loot tables")?)?;
+					let weight = weight(rest.get(1))?;
+					let item = parse_item(rest.get(2..).unwrap_or(&[]), rarity)?;
+					table.entries.push(LootEntry { rarity, weight, item });
+				}
+				_ => return Err("unknown directive in loot tables"),
+			};
+		}
+		return Ok(tables);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const TABLES: &str = "
+		chance floor 1
+		rarity floor common 3
+		rarity floor rare 1
+		item floor common 1 pistol
+		item floor common 1 ammo light 30
+		item floor common 1 material wood 50
+		item floor rare 1 consumable medkit 1
+		item floor rare 1 grenade 3
+	";
+
+	// everything the first few floor spawns give for `seed`
+	fn layout(tables: &LootTables, seed: u64) -> String {
+		let items = (0..8).map(|spawn| tables.floor.roll(&mut(Rng::stream(seed, crate::rng::LOOT_FLOOR | spawn)))).collect::<Vec<_>>();
+		return format!("{items:?}");
+	}
+
+	#[test]
+	fn same_seed_same_loot() {
+		let tables = LootTables::parse(TABLES).unwrap();
+		assert_eq!(layout(&(tables), 1), layout(&(tables), 1));
+		assert_ne!(layout(&(tables), 1), layout(&(tables), 2));
+		return;
+	}
+
+	#[test]
+	fn bad_lines_are_rejected() {
+		for (line, err) in [
+			("item floor shiny 1 pistol", "unknown rarity in loot tables"),
+			("item floor common 1 ammo light", "loot item needs a count"),
+			("item floor common 1 ammo light lots", "invalid count in loot tables"),
+			("item floor common 1 ammo light -30", "invalid count in loot tables"),
+			("chance floor 1.5", "loot chance must be between 0 and 1"),
+			("chance floor -0.5", "loot chance must be between 0 and 1"),
+			("rarity floor common 0", "loot weights must be positive"),
+			("item floor common -1 pistol", "loot weights must be positive"),
+			("item floor common 1 laser", "unknown item in loot tables"),
+			("item floor common 1", "loot item is missing"),
+			("rarity floor common", "missing number in loot tables"),
+			("chance attic 1", "unknown loot table"),
+			("spawn floor 1", "unknown directive in loot tables"),
+			("chance", "loot table line needs a directive and a table"),
+		] {
+			assert_eq!(LootTables::parse(line).err(), Some(err), "{line}");
+		}
+		return;
+	}
+}
@@ -53,15 +53,38 @@ use {
 };
 
 mod state;
+mod build;
 mod camera;
+mod camera_effects;
+mod camera_path;
+mod collision;
+mod debug_camera;
 mod input;
+mod local_player;
 mod player;
+mod inventory;
+mod pickup;
+mod chest;
+mod loot;
+mod storm;
+mod bus;
+mod match_controller;
+mod navmesh;
+mod bot;
+mod projectile;
+mod rng;
+mod prop;
 mod texture;
+mod weapon;
 mod obj;
+mod settings;
 
 use input::Input;
+use settings::Settings;
 use state::State;
 
+const CAMERA_PATH: &str = "camera_path.txt";
+
 fn handle_window_event(state: &mut State, event: WindowEvent) -> ControlFlow {
 	use WindowEvent::{*, KeyboardInput as KeyboardInputEvent};
 	use winit::event::{KeyEvent, MouseButton};
@@ -76,6 +99,12 @@ fn handle_window_event(state: &mut State, event: WindowEvent) -> ControlFlow {
 					state.set_focus(true);
 				}
 			}
+			if state.is_focused() {
+				state.process_mouse_button(button, elem_state);
+			}
+		}
+		MouseWheel { delta, .. } if state.is_focused() => {
+			state.process_mouse_wheel(delta);
 		}
 		CursorLeft { .. } if state.is_focused() => {
 			state.set_focus(false);
@@ -103,6 +132,31 @@ fn handle_window_event(state: &mut State, event: WindowEvent) -> ControlFlow {
 				// toggle fullscreen
 				state.set_fullscreen(!state.is_fullscreen());
 			}
+			KeyCode::F1 if elem_state == ElementState::Pressed && state.is_focused() => {
+				// detach/reattach the debug camera
+				state.toggle_debug_camera();
+			}
+			KeyCode::F2 if elem_state == ElementState::Pressed && state.is_focused() => {
+				// move the debug camera back to the player
+				state.snap_debug_camera();
+			}
+			KeyCode::F3 if elem_state == ElementState::Pressed && state.is_focused() => {
+				// start recording, or stop and save
+				if let Err(e) = state.toggle_camera_recording(CAMERA_PATH) {
+					eprintln!("{e}");
+				}
+			}
+			KeyCode::F4 if elem_state == ElementState::Pressed && state.is_focused() => {
+				if let Err(e) = state.toggle_camera_playback(CAMERA_PATH) {
+					eprintln!("{e}");
+				}
+			}
+			KeyCode::BracketLeft if elem_state == ElementState::Pressed && state.is_focused() => {
+				state.scale_camera_playback_speed(0.5);
+			}
+			KeyCode::BracketRight if elem_state == ElementState::Pressed && state.is_focused() => {
+				state.scale_camera_playback_speed(2.0);
+			}
 			_  => if state.is_focused() { state.process_key(key, elem_state); }
 		}
 
@@ -112,16 +166,41 @@ fn handle_window_event(state: &mut State, event: WindowEvent) -> ControlFlow {
 	return ControlFlow::Poll;
 }
 
+// game --render-path <camera path> <output directory> [fps]
+fn render_path_args() -> Result<Option<(camera_path::CameraPath, String, f32)>, &'static str> {
+	let args = std::env::args().collect::<Vec<String>>();
+	if args.get(1).map(|a| a.as_str()) != Some("--render-path") {
+		return Ok(None);
+	}
+	let path = camera_path::CameraPath::load(args.get(2).ok_or("missing camera path")?)?;
+	let out_dir = args.get(3).ok_or("missing output directory")?.clone();
+	let fps = match args.get(4) {
+		Some(fps) => fps.parse().map_err(|_| "invalid fps")?,
+		None => 60.0,
+	};
+	return Ok(Some((path, out_dir, fps)));
+}
+
 fn real_main() -> Result<(), &'static str> {
+	let render_path = render_path_args()?;
+
 	let event_loop = EventLoop::new();
 	let window = WindowBuilder::new()
 		.with_title("game")
 		.with_fullscreen(None)
+		.with_visible(render_path.is_none())
 		.build(&(event_loop))
 		.map_err(|_| "failed to create window")?;
 	let window_id = window.id();
 
-	let mut state = State::new(window, Input::new(1.0, 9.21 * 800.0))?;
+	let settings = Settings::from_env()?;
+	let debug_stats = settings.debug_stats;
+	let mut state = State::new(window, Input::new(1.0, 9.21 * 800.0), settings)?;
+
+	if let Some((path, out_dir, fps)) = render_path {
+		// nothing is presented, the window only exists to get a device
+		return state.render_camera_path(path, fps, out_dir);
+	}
 
 	let mut total_elapsed = 0.0;
 	let mut frames = 0;
@@ -163,6 +242,11 @@ fn real_main() -> Result<(), &'static str> {
 
 				while elapsed >= TIMESTEP {
 					state.update_camera(TIMESTEP, sf);
+					state.update_bots(TIMESTEP);
+					state.update_projectiles(TIMESTEP);
+					state.update_storm(TIMESTEP);
+					state.update_bus(TIMESTEP);
+					state.update_match(TIMESTEP);
 
 					elapsed -= TIMESTEP;
 					interpolate -= sf;
@@ -184,7 +268,21 @@ fn real_main() -> Result<(), &'static str> {
 				frames += 1;
 				if total_elapsed >= 1.0 {
 					println!("frames in the past {total_elapsed}s: {frames:?}");
-					println!("camera at {:?}", state.camera.position);
+					if debug_stats {
+						let controller = &(state.match_controller);
+						println!("{:?} match {:?} for {:.0}s, {:?} left, seed {}", controller.mode, controller.phase, controller.elapsed, controller.counts, state.seed);
+						let circle = state.storm.circle();
+						println!("storm phase {}, {:.0} around {:?}", state.storm.phase, circle.radius, circle.center);
+					}
+					for local_player in &(state.local_players) {
+						println!("camera at {:?}", local_player.camera.position);
+						if debug_stats {
+							let player = &(state.players[local_player.player]);
+							println!("{:?}, {:?} ({:?} selected)", player.movement_state(), player.inventory.resources, player.build_material);
+							println!("health {:.0}, shield {:.0} ({:?}), using {:?}, reviving {:?}", player.health, player.shield, player.life, player.using, player.reviving);
+							println!("slot {:?} of {:?}, ammo {:?}", player.inventory.selected, player.inventory.slots.map(|slot| slot.is_some()), player.inventory.ammo);
+						}
+					}
 					frames = 0;
 					total_elapsed = 0.0;
 				}
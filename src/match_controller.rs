@@ -0,0 +1,185 @@
+use crate::player::{Player, Life};
+
+// seconds on the warmup island before the bus leaves
+const WARMUP_TIME: f32 = 30.0;
+// seconds the winner gets before the match is over
+const VICTORY_TIME: f32 = 5.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TeamMode {
+	Solo,
+	Duos,
+	Squads,
+}
+
+impl TeamMode {
+	pub fn team_size(&self) -> usize {
+		return match self {
+			Self::Solo => 1,
+			Self::Duos => 2,
+			Self::Squads => 4,
+		};
+	}
+
+	// teams are filled up in the order players joined
+	pub fn team(&self, player: usize) -> usize {
+		return player / self.team_size();
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchPhase {
+	// waiting for everyone to join
+	Lobby,
+	// messing around on the warmup island, where nothing counts
+	Warmup,
+	// everyone's in the bus, or dropping out of it
+	Bus,
+	InProgress,
+	// the last team standing, or None if nobody was left
+	Victory { team: Option<usize> },
+	// over, and straight back to the lobby for the next one
+	Ended,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchEvent {
+	PhaseChanged(MatchPhase),
+	// whenever someone is out once the match has started
+	PlayersRemaining(PlayerCounts),
+}
+
+// how many players and teams are still in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PlayerCounts {
+	pub players: usize,
+	pub teams: usize,
+}
+
+impl PlayerCounts {
+	pub fn of(players: &[Player]) -> Self {
+		return Self {
+			players: players.iter().filter(|player| player.life != Life::Eliminated).count(),
+			teams: remaining_teams(players).len(),
+		};
+	}
+}
+
+// every team with anyone not eliminated, in order
+fn remaining_teams(players: &[Player]) -> Vec<usize> {
+	let mut teams = players
+		.iter()
+		.filter(|player| player.life != Life::Eliminated)
+		.map(|player| player.team)
+		.collect::<Vec<_>>();
+	teams.sort();
+	teams.dedup();
+	return teams;
+}
+
+pub struct MatchController {
+	pub mode: TeamMode,
+	pub phase: MatchPhase,
+	// into the current phase
+	pub elapsed: f32,
+	pub counts: PlayerCounts,
+	// the lobby waits for this many players
+	pub expected_players: usize,
+	// a match that started with one team is played until they're out, rather than won straight away
+	starting_teams: usize,
+	events: Vec<MatchEvent>,
+}
+
+impl MatchController {
+	pub fn new(mode: TeamMode, expected_players: usize) -> Self {
+		return Self {
+			mode,
+			phase: MatchPhase::Lobby,
+			elapsed: 0.0,
+			counts: PlayerCounts::default(),
+			expected_players,
+			starting_teams: 0,
+			events: Vec::new(),
+		};
+	}
+
+	fn set_phase(&mut self, phase: MatchPhase) {
+		self.phase = phase;
+		self.elapsed = 0.0;
+		self.events.push(MatchEvent::PhaseChanged(phase));
+		return;
+	}
+
+	// returns what happened, for everything else to react to.
+	// `bus_finished` is whether the bus has dropped everyone.
+	pub fn update(&mut self, dt: f32, players: &[Player], bus_finished: bool) -> Vec<MatchEvent> {
+		self.elapsed += dt;
+		let counts = PlayerCounts::of(players);
+		let started = matches!(self.phase, MatchPhase::Bus | MatchPhase::InProgress);
+		if started && counts != self.counts {
+			self.events.push(MatchEvent::PlayersRemaining(counts));
+		}
+		self.counts = counts;
+
+		match self.phase {
+			MatchPhase::Lobby if players.len() >= self.expected_players => self.set_phase(MatchPhase::Warmup),
+			MatchPhase::Warmup if self.elapsed >= WARMUP_TIME => {
+				self.starting_teams = counts.teams;
+				self.set_phase(MatchPhase::Bus);
+			}
+			MatchPhase::Bus | MatchPhase::InProgress => {
+				let teams = remaining_teams(players);
+				if teams.is_empty() {
+					self.set_phase(MatchPhase::Victory { team: None });
+				} else if teams.len() == 1 && self.starting_teams > 1 {
+					self.set_phase(MatchPhase::Victory { team: Some(teams[0]) });
+				} else if self.phase == MatchPhase::Bus && bus_finished {
+					self.set_phase(MatchPhase::InProgress);
+				}
+			}
+			MatchPhase::Victory { .. } if self.elapsed >= VICTORY_TIME => self.set_phase(MatchPhase::Ended),
+			MatchPhase::Ended => self.set_phase(MatchPhase::Lobby),
+			_ => (),
+		};
+		return std::mem::take(&mut(self.events));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use cgmath::{Point3, Deg};
+
+	#[test]
+	fn ended_matches_go_back_to_warmup() {
+		let mut players = vec![Player::new(Point3::new(0.0, 0.0, 0.0), Deg(0.0), 0)];
+		let mut controller = MatchController::new(TeamMode::Solo, 1);
+		controller.update(0.0, &(players), false);
+		assert_eq!(controller.phase, MatchPhase::Warmup);
+		controller.update(WARMUP_TIME, &(players), false);
+		assert_eq!(controller.phase, MatchPhase::Bus);
+
+		// playing on alone until they're out
+		players[0].life = Life::Eliminated;
+		controller.update(0.0, &(players), false);
+		assert_eq!(controller.phase, MatchPhase::Victory { team: None });
+		controller.update(VICTORY_TIME, &(players), false);
+		assert_eq!(controller.phase, MatchPhase::Ended);
+
+		assert_eq!(controller.update(0.0, &(players), false), vec![MatchEvent::PhaseChanged(MatchPhase::Lobby)]);
+		assert_eq!(controller.update(0.0, &(players), false), vec![MatchEvent::PhaseChanged(MatchPhase::Warmup)]);
+		return;
+	}
+
+	#[test]
+	fn lobby_waits_for_everyone() {
+		let mut players = vec![Player::new(Point3::new(0.0, 0.0, 0.0), Deg(0.0), 0)];
+		let mut controller = MatchController::new(TeamMode::Duos, 2);
+		controller.update(10.0, &(players), false);
+		assert_eq!(controller.phase, MatchPhase::Lobby);
+		players.push(Player::new(Point3::new(1.0, 0.0, 0.0), Deg(0.0), 0));
+		controller.update(0.0, &(players), false);
+		assert_eq!(controller.phase, MatchPhase::Warmup);
+		return;
+	}
+}
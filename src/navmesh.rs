@@ -0,0 +1,295 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+use cgmath::{Point2, Point3};
+
+use crate::{
+	player::{self, Ground},
+	storm::Circle,
+	collision::Aabb,
+};
+
+// anything under a cell taller than this is in the way
+const MAX_STEP: f32 = 0.35;
+// how high above the ground to look for things in the way
+const PROBE_HEIGHT: f32 = 20.0;
+// how many cells away to look for somewhere walkable when a path starts or ends somewhere that isn't
+const SNAP_CELLS: usize = 4;
+
+// square cells over the ground, each either walkable or not.
+// built once the map's put together, then patched up wherever it changes.
+pub struct NavMesh {
+	// the corner with the smallest x and z
+	origin: Point2<f32>,
+	cell: f32,
+	width: usize,
+	depth: usize,
+	walkable: Vec<bool>,
+}
+
+impl NavMesh {
+	// nothing is walkable, so every path has to be walked straight
+	pub fn empty() -> Self {
+		return Self { origin: Point2::new(0.0, 0.0), cell: 1.0, width: 0, depth: 0, walkable: Vec::new() };
+	}
+
+	// covers the square around `area`
+	pub fn build<G: Ground>(ground: &G, area: &Circle, cell: f32) -> Self {
+		let size = (2.0 * area.radius / cell).ceil() as usize;
+		let origin = area.center - cgmath::Vector2::new(area.radius, area.radius);
+		let mut mesh = Self { origin, cell, width: size, depth: size, walkable: vec![false; size * size] };
+		for index in 0..mesh.walkable.len() {
+			mesh.walkable[index] = mesh.probe(ground, index);
+		}
+		return mesh;
+	}
+
+	// the highest thing under the cell, which has to be floor
+	fn probe<G: Ground>(&self, ground: &G, cell: usize) -> bool {
+		let hit = ground.ground_below(self.center(cell), player::RADIUS, PROBE_HEIGHT, MAX_STEP);
+		return hit.is_some_and(|hit| hit.height <= MAX_STEP);
+	}
+
+	// looks again at every cell whose probe could touch `area`, after something there changed
+	pub fn update_area<G: Ground>(&mut self, ground: &G, area: Aabb) {
+		let area = area.expand(player::RADIUS);
+		let first = |min: f32, origin: f32| ((min - origin) / self.cell - 0.5).ceil().max(0.0) as usize;
+		let last = |max: f32, origin: f32, count: usize| (((max - origin) / self.cell - 0.5).floor() + 1.0).clamp(0.0, count as f32) as usize;
+		for z in first(area.min.z, self.origin.y)..last(area.max.z, self.origin.y, self.depth) {
+			for x in first(area.min.x, self.origin.x)..last(area.max.x, self.origin.x, self.width) {
+				let cell = z * self.width + x;
+				self.walkable[cell] = self.probe(ground, cell);
+			}
+		}
+		return;
+	}
+
+	fn cell_at(&self, position: Point3<f32>) -> Option<usize> {
+		let x = ((position.x - self.origin.x) / self.cell).floor();
+		let z = ((position.z - self.origin.y) / self.cell).floor();
+		if x < 0.0 || z < 0.0 || x as usize >= self.width || z as usize >= self.depth {
+			return None;
+		}
+		return Some(z as usize * self.width + x as usize);
+	}
+
+	fn center(&self, cell: usize) -> Point3<f32> {
+		let (x, z) = (cell % self.width, cell / self.width);
+		return Point3::new(self.origin.x + (x as f32 + 0.5) * self.cell, 0.0, self.origin.y + (z as f32 + 0.5) * self.cell);
+	}
+
+	fn is_walkable(&self, x: isize, z: isize) -> bool {
+		if x < 0 || z < 0 || x as usize >= self.width || z as usize >= self.depth {
+			return false;
+		}
+		return self.walkable[z as usize * self.width + x as usize];
+	}
+
+	// `cell` itself if it's walkable, otherwise the first walkable one found going outwards
+	fn nearest_walkable(&self, cell: usize) -> Option<usize> {
+		let (x, z) = ((cell % self.width) as isize, (cell / self.width) as isize);
+		for ring in 0..=SNAP_CELLS as isize {
+			for dz in -ring..=ring {
+				for dx in -ring..=ring {
+					if dx.abs().max(dz.abs()) == ring && self.is_walkable(x + dx, z + dz) {
+						return Some((z + dz) as usize * self.width + (x + dx) as usize);
+					}
+				}
+			}
+		}
+		return None;
+	}
+
+	// A* over the cells, moving in 8 directions without cutting corners.
+	// returns the points to walk through, not including where it starts.
+	pub fn find_path(&self, from: Point3<f32>, to: Point3<f32>) -> Option<Vec<Point3<f32>>> {
+		let start = self.nearest_walkable(self.cell_at(from)?)?;
+		let goal_cell = self.cell_at(to)?;
+		let goal = self.nearest_walkable(goal_cell)?;
+
+		// costs are in tenths of a cell so they stay whole numbers
+		let coords = |cell: usize| ((cell % self.width) as isize, (cell / self.width) as isize);
+		let (goal_x, goal_z) = coords(goal);
+		let heuristic = |cell: usize| {
+			let (x, z) = coords(cell);
+			let (dx, dz) = ((x - goal_x).unsigned_abs() as u32, (z - goal_z).unsigned_abs() as u32);
+			return 10 * dx.max(dz) + 4 * dx.min(dz);
+		};
+
+		let mut cost = vec![u32::MAX; self.walkable.len()];
+		let mut came_from = vec![usize::MAX; self.walkable.len()];
+		let mut open = BinaryHeap::new();
+		cost[start] = 0;
+		open.push(Reverse((heuristic(start), start)));
+		while let Some(Reverse((_, cell))) = open.pop() {
+			if cell == goal {
+				let mut path = Vec::new();
+				let mut at = goal;
+				while at != start {
+					path.push(self.center(at));
+					at = came_from[at];
+				}
+				path.reverse();
+				// the exact spot if it could be walked to
+				if goal == goal_cell {
+					if let Some(last) = path.last_mut() {
+						*last = to;
+					}
+				}
+				return Some(path);
+			}
+
+			let (x, z) = coords(cell);
+			for (dx, dz) in [(1, 0), (-1, 0), (0, 1), (0, -1), (1, 1), (1, -1), (-1, 1), (-1, -1)] {
+				if !self.is_walkable(x + dx, z + dz) {
+					continue;
+				}
+				let diagonal = dx != 0 && dz != 0;
+				if diagonal && !(self.is_walkable(x + dx, z) && self.is_walkable(x, z + dz)) {
+					continue;
+				}
+				let next = (z + dz) as usize * self.width + (x + dx) as usize;
+				let next_cost = cost[cell] + if diagonal { 14 } else { 10 };
+				if next_cost < cost[next] {
+					cost[next] = next_cost;
+					came_from[next] = cell;
+					open.push(Reverse((next_cost + heuristic(next), next)));
+				}
+			}
+		}
+		return None;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// one row per z, '#' for anything in the way
+	fn grid(rows: &[&str]) -> NavMesh {
+		let walkable = rows.iter().flat_map(|row| row.chars().map(|c| c != '#')).collect();
+		return NavMesh { origin: Point2::new(0.0, 0.0), cell: 1.0, width: rows[0].len(), depth: rows.len(), walkable };
+	}
+
+	fn at(x: f32, z: f32) -> Point3<f32> {
+		return Point3::new(x, 0.0, z);
+	}
+
+	// every step goes to a walkable neighbour, without squeezing diagonally past anything
+	fn check_steps(mesh: &NavMesh, from: Point3<f32>, path: &[Point3<f32>]) {
+		let cells = std::iter::once(from).chain(path.iter().copied()).map(|p| (p.x.floor() as isize, p.z.floor() as isize)).collect::<Vec<_>>();
+		for pair in cells.windows(2) {
+			let ((x, z), (next_x, next_z)) = (pair[0], pair[1]);
+			let (dx, dz) = (next_x - x, next_z - z);
+			assert!(dx.abs() <= 1 && dz.abs() <= 1, "{cells:?}");
+			assert!(mesh.is_walkable(next_x, next_z), "{cells:?}");
+			if dx != 0 && dz != 0 {
+				assert!(mesh.is_walkable(x + dx, z) && mesh.is_walkable(x, z + dz), "{cells:?}");
+			}
+		}
+		return;
+	}
+
+	#[test]
+	fn goes_around_a_wall() {
+		let mesh = grid(&[
+			".....",
+			"..#..",
+			"..#..",
+			"..#..",
+			".....",
+		]);
+		let (from, to) = (at(0.5, 2.5), at(4.5, 2.5));
+		let path = mesh.find_path(from, to).unwrap();
+		check_steps(&(mesh), from, &(path));
+		assert_eq!(path.last(), Some(&(to)));
+		// straight across would be 4 steps
+		assert_eq!(path.len(), 6);
+		return;
+	}
+
+	#[test]
+	fn never_cuts_corners() {
+		let mesh = grid(&[
+			"....",
+			".##.",
+			"....",
+		]);
+		let from = at(0.5, 1.5);
+		let path = mesh.find_path(from, at(3.5, 1.5)).unwrap();
+		check_steps(&(mesh), from, &(path));
+
+		// a diagonal gap isn't a gap at all
+		let mesh = grid(&[
+			".#",
+			"#.",
+		]);
+		assert!(mesh.find_path(at(0.5, 0.5), at(1.5, 1.5)).is_none());
+		return;
+	}
+
+	#[test]
+	fn snaps_to_the_nearest_walkable_cell() {
+		let mesh = grid(&[
+			".....",
+			".....",
+			"..###",
+			"..###",
+			"..###",
+		]);
+		// starting inside, the way out is to the left
+		let path = mesh.find_path(at(2.5, 3.5), at(0.5, 3.5)).unwrap();
+		check_steps(&(mesh), at(1.5, 3.5), &(path));
+		assert_eq!(path, vec![at(0.5, 3.5)]);
+
+		// ending inside, it goes as close as it can instead of the exact spot
+		let to = at(4.5, 4.5);
+		let path = mesh.find_path(at(0.5, 0.5), to).unwrap();
+		let last = *path.last().unwrap();
+		assert_ne!(last, to);
+		assert!(mesh.is_walkable(last.x.floor() as isize, last.z.floor() as isize));
+		// the first ring out with anything walkable in it
+		assert_eq!((last.x - to.x).abs().max((last.z - to.z).abs()), 3.0);
+		return;
+	}
+
+	#[test]
+	fn nothing_when_unreachable() {
+		let mesh = grid(&[
+			".....",
+			".###.",
+			".#.#.",
+			".###.",
+			".....",
+		]);
+		assert!(mesh.find_path(at(0.5, 0.5), at(2.5, 2.5)).is_none());
+		// off the mesh altogether
+		assert!(mesh.find_path(at(0.5, 0.5), at(20.0, 0.5)).is_none());
+		assert!(mesh.find_path(at(-3.0, 0.5), at(0.5, 0.5)).is_none());
+		return;
+	}
+
+	#[test]
+	fn building_blocks_cells() {
+		use crate::collision::{CollisionWorld, cuboid};
+
+		// a 10x10 floor with its top at 0, from the origin
+		let mut collision = CollisionWorld::new(cuboid(Point3::new(5.0, -1.0, 5.0), cgmath::Vector3::new(10.0, 1.0, 10.0)));
+		let mut mesh = NavMesh::build(&(collision), &(Circle { center: Point2::new(5.0, 5.0), radius: 5.0 }), 1.0);
+		assert!(mesh.walkable.iter().all(|&walkable| walkable));
+
+		// a wall across x = 5..6 from z = 0 to 6
+		let wall = cuboid(Point3::new(5.5, 0.0, 3.0), cgmath::Vector3::new(1.0, 2.0, 6.0));
+		let area = wall.iter().map(|triangle| triangle.aabb()).reduce(Aabb::union).unwrap();
+		collision.add_dynamic(wall);
+		mesh.update_area(&(collision), area);
+		for z in 0..10 {
+			for x in 0..10 {
+				assert_eq!(mesh.is_walkable(x, z), !(x == 5 && z < 6), "{x} {z}");
+			}
+		}
+
+		let path = mesh.find_path(at(4.5, 0.5), at(6.5, 0.5)).unwrap();
+		check_steps(&(mesh), at(4.5, 0.5), &(path));
+		assert!(path.iter().any(|point| point.z > 6.0));
+		return;
+	}
+}
@@ -28,6 +28,30 @@ pub struct Material {
 	pub bind_group: wgpu::BindGroup,
 }
 
+impl Material {
+	pub fn new(device: &wgpu::Device, layout: &wgpu::BindGroupLayout, diffuse_texture: Texture) -> Self {
+		let bind_group = device.create_bind_group(&(wgpu::BindGroupDescriptor {
+			layout,
+			entries: &[
+				wgpu::BindGroupEntry {
+					binding: 0,
+					resource: wgpu::BindingResource::TextureView(&(diffuse_texture.view)),
+				},
+				wgpu::BindGroupEntry {
+					binding: 1,
+					resource: wgpu::BindingResource::Sampler(&(diffuse_texture.sampler)),
+				},
+			],
+			label: None,
+		}));
+		return Self { diffuse_texture, bind_group };
+	}
+
+	pub fn solid(device: &wgpu::Device, queue: &wgpu::Queue, layout: &wgpu::BindGroupLayout, rgba: u32, label: Option<&str>) -> Self {
+		return Self::new(device, layout, Texture::solid(device, queue, rgba, label));
+	}
+}
+
 pub struct Mesh {
 	pub vertex_buffer: wgpu::Buffer,
 	pub index_buffer: wgpu::Buffer,
@@ -59,25 +83,7 @@ pub fn load_obj(
 		} else {
 			Texture::solid(device, queue, 0xff0000ff, None)
 		};
-		let bind_group = device.create_bind_group(&(wgpu::BindGroupDescriptor {
-			layout,
-			entries: &[
-				wgpu::BindGroupEntry {
-					binding: 0,
-					resource: wgpu::BindingResource::TextureView(&(diffuse_texture.view)),
-				},
-				wgpu::BindGroupEntry {
-					binding: 1,
-					resource: wgpu::BindingResource::Sampler(&(diffuse_texture.sampler)),
-				},
-			],
-			label: None,
-		}));
-
-		materials.push(Material {
-			diffuse_texture,
-			bind_group,
-		})
+		materials.push(Material::new(device, layout, diffuse_texture));
 	}
 
 	// models is a Vec of struct { mesh: Mesh, name: String }
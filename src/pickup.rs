@@ -0,0 +1,108 @@
+use cgmath::{Point3, Vector3, InnerSpace};
+
+use crate::{
+	collision::cuboid,
+	inventory::Item,
+	loot::Rarity,
+	obj::Vertex,
+};
+
+// how far from where they were dropped things end up
+const DROP_RADIUS: f32 = 1.0;
+
+// width, height, depth
+fn size(item: &Item) -> Vector3<f32> {
+	return match item {
+		Item::Weapon(_) => Vector3::new(0.7, 0.2, 0.2),
+		Item::Consumable(..) => Vector3::new(0.3, 0.3, 0.3),
+		Item::Ammo(..) => Vector3::new(0.3, 0.2, 0.3),
+		Item::Material(..) => Vector3::new(0.4, 0.4, 0.4),
+	};
+}
+
+pub const GROUPS: usize = 5 + Rarity::ALL.len();
+
+// which group it's drawn in: wood, brick, metal, ammo, consumables, then weapons by rarity
+fn group(item: &Item) -> usize {
+	return match item {
+		Item::Material(material, _) => material.index(),
+		Item::Ammo(..) => 3,
+		Item::Consumable(..) => 4,
+		Item::Weapon(weapon) => 5 + weapon.rarity.index(),
+	};
+}
+
+// something lying on the ground to be picked up
+#[derive(Debug, Clone, Copy)]
+pub struct Pickup {
+	pub item: Item,
+	// centre of its base
+	pub position: Point3<f32>,
+}
+
+pub struct Pickups {
+	pub pickups: Vec<Pickup>,
+	// bumped on every change, see Buildings
+	generation: u64,
+}
+
+impl Pickups {
+	pub fn new() -> Self {
+		return Self { pickups: Vec::new(), generation: 0 };
+	}
+
+	pub fn generation(&self) -> u64 {
+		return self.generation;
+	}
+
+	pub fn place(&mut self, position: Point3<f32>, item: Item) {
+		self.pickups.push(Pickup { item, position });
+		self.generation += 1;
+		return;
+	}
+
+	pub fn clear(&mut self) {
+		self.pickups.clear();
+		self.generation += 1;
+		return;
+	}
+
+	// spreads the items out in a circle around `center`
+	pub fn drop_items(&mut self, center: Point3<f32>, items: Vec<Item>) {
+		let count = items.len();
+		for (index, item) in items.into_iter().enumerate() {
+			let angle = std::f32::consts::TAU * index as f32 / count as f32;
+			self.place(center + Vector3::new(angle.cos(), 0.0, angle.sin()) * DROP_RADIUS, item);
+		}
+		return;
+	}
+
+	// the closest one no further than `reach` from `position`
+	pub fn nearest(&self, position: Point3<f32>, reach: f32) -> Option<usize> {
+		return self.pickups
+			.iter()
+			.enumerate()
+			.map(|(index, pickup)| (index, (pickup.position - position).magnitude()))
+			.filter(|&(_, distance)| distance <= reach)
+			.min_by(|(_, a), (_, b)| a.total_cmp(b))
+			.map(|(index, _)| index);
+	}
+
+	pub fn take(&mut self, index: usize) -> Pickup {
+		self.generation += 1;
+		return self.pickups.swap_remove(index);
+	}
+
+	pub fn vertices(&self) -> [Vec<Vertex>; GROUPS] {
+		let mut vertices: [Vec<Vertex>; GROUPS] = std::array::from_fn(|_| Vec::new());
+		for pickup in &(self.pickups) {
+			let group = &mut(vertices[group(&(pickup.item))]);
+			for triangle in cuboid(pickup.position, size(&(pickup.item))) {
+				for position in [triangle.a, triangle.b, triangle.c] {
+					group.push(Vertex { position: position.into(), tex_coords: [0.0, 0.0] });
+				}
+			}
+		}
+		return vertices;
+	}
+}
@@ -1,16 +1,243 @@
-use cgmath::{Point3, Deg, Vector3, Rad, InnerSpace};
-use crate::input::Input;
+use cgmath::{Point3, Deg, Vector3, Rad, InnerSpace, Zero};
+use crate::{
+	input::Input,
+	build::{PieceKind, EditSession, BuildMaterial},
+	collision::{Capsule, cuboid},
+	obj::Vertex,
+	weapon::{Weapon, WeaponKind, DamageKind},
+	inventory::{Inventory, Item, AmmoKind, ConsumableKind},
+	loot::Rarity,
+	chest::ChestId,
+};
+
+// eye height above the player's feet
+const EYE_HEIGHT: f32 = 1.25;
+const CROUCH_EYE_HEIGHT: f32 = 0.8;
+// how fast the eyes move between the two (units/s)
+const EYE_SPEED: f32 = 4.0;
+
+const WALK_SPEED: f32 = 4.0;
+const SPRINT_SPEED: f32 = 6.5;
+const CROUCH_SPEED: f32 = 2.0;
+// sliding starts when crouching at (nearly) sprint speed, with a little push
+const SLIDE_MIN_SPEED: f32 = 6.0;
+const SLIDE_BOOST: f32 = 1.5;
+const SLIDE_FRICTION: f32 = 3.0;
+const SLIDE_STEER: f32 = 2.0;
+const GROUND_ACCEL: f32 = 40.0;
+const AIR_ACCEL: f32 = 8.0;
+const GRAVITY: f32 = 20.0;
+const JUMP_SPEED: f32 = 7.0;
+// ledges up to this high are stepped onto instead of blocking
+const STEP_HEIGHT: f32 = 0.35;
+// how far below the feet ground is still considered "under" the player
+// while walking, so walking down slopes/stairs doesn't turn into falling
+const GROUND_SNAP: f32 = 0.2;
+const MAX_SLOPE: Deg<f32> = Deg(46.0);
+pub const RADIUS: f32 = 0.3;
+pub const HEIGHT: f32 = 1.5;
+pub const CROUCH_HEIGHT: f32 = 1.0;
+pub const HEAD_RADIUS: f32 = 0.2;
+
+pub const MAX_HEALTH: f32 = 100.0;
+pub const MAX_SHIELD: f32 = 100.0;
+// knocked players get a fresh pool that bleeds away
+const KNOCKED_HEALTH: f32 = 100.0;
+pub const BLEED_RATE: f32 = 5.0;
+const KNOCKED_SPEED: f32 = 1.2;
+// seconds interact has to be held next to a knocked teammate, and what they get back up with
+pub const REVIVE_TIME: f32 = 5.0;
+const REVIVE_HEALTH: f32 = 30.0;
+// landing faster than this hurts, more the faster it was
+const SAFE_FALL_SPEED: f32 = 16.0;
+const FALL_DAMAGE_PER_SPEED: f32 = 8.0;
+// falling out of the bus: steady fall speeds (air resistance) and how fast it can be steered
+const SKYDIVE_FALL_SPEED: f32 = 20.0;
+const SKYDIVE_SPEED: f32 = 10.0;
+// holding forward trades steering for getting down sooner
+const DIVE_FALL_SPEED: f32 = 35.0;
+const DIVE_SPEED: f32 = 18.0;
+const GLIDE_FALL_SPEED: f32 = 5.0;
+const GLIDE_SPEED: f32 = 12.0;
+const FALL_STEER_ACCEL: f32 = 12.0;
+// how quickly the fall slows down to its steady speed, e.g. once the glider opens
+const AIR_DRAG: f32 = 50.0;
+// the glider opens by itself this close to the ground
+const GLIDER_HEIGHT: f32 = 30.0;
+
+pub struct GroundHit {
+	pub height: f32,
+	pub normal: Vector3<f32>,
+}
+
+pub trait Ground {
+	// the highest surface under a circle of `radius` around `position`,
+	// that is no higher than `position.y + max_up` and no lower than `position.y - max_down`
+	fn ground_below(&self, position: Point3<f32>, radius: f32, max_up: f32, max_down: f32) -> Option<GroundHit>;
+	// moves an upright body standing at `position` by `motion`,
+	// returning how far it got (0..1) and the normal of what stopped it
+	fn sweep_body(&self, position: Point3<f32>, radius: f32, height: f32, motion: Vector3<f32>) -> Option<(f32, Vector3<f32>)>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveMode {
+	Walking,
+	// free flight through everything, for debugging
+	Noclip,
+	// carried along by the bus until jumping out
+	InBus,
+	Skydiving,
+	Gliding,
+}
+
+impl MoveMode {
+	// on the way into the match, with nothing usable
+	pub fn is_dropping(&self) -> bool {
+		return matches!(self, Self::InBus | Self::Skydiving | Self::Gliding);
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stance {
+	Standing,
+	Crouching,
+	Sliding,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Life {
+	Alive,
+	// down but not out, while a teammate is still up.
+	// crawls around and can't use anything.
+	Knocked { health: f32, cause: DamageKind },
+	Eliminated,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct EliminationEvent {
+	// indices into State::players
+	pub victim: usize,
+	pub killer: Option<usize>,
+	// what finished them, or what knocked them if their team went down around them
+	pub kind: DamageKind,
+}
+
+// a consumable being channelled
+#[derive(Debug, Clone, Copy)]
+pub struct Using {
+	// the hotbar slot it's in, it stops if that's no longer selected
+	pub slot: usize,
+	pub kind: ConsumableKind,
+	pub elapsed: f32,
+}
+
+// what the player is doing, e.g. for picking animations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MovementState {
+	Idle,
+	Walking,
+	Sprinting,
+	CrouchIdle,
+	CrouchWalking,
+	Sliding,
+	Airborne,
+	Noclip,
+	InBus,
+	Skydiving,
+	Gliding,
+}
 
 pub struct Player {
+	// at the player's feet
 	pub position: Point3<f32>,
 	pub rot_x: Deg<f32>,
+
+	pub velocity: Vector3<f32>,
+	pub grounded: bool,
+	pub ground_normal: Vector3<f32>,
+	pub move_mode: MoveMode,
+	pub stance: Stance,
+	pub sprinting: bool,
+	pub eye_height: f32,
+
+	pub health: f32,
+	pub shield: f32,
+	pub life: Life,
+	// players on the same team can knock each other down instead of eliminating
+	pub team: usize,
+	// who gets the elimination if nobody else finishes the player off
+	pub last_attacker: Option<usize>,
+
+	// the piece being previewed/placed, if in build mode
+	pub building: Option<PieceKind>,
+	pub build_cooldown: f32,
+	pub editing: Option<EditSession>,
+	pub build_material: BuildMaterial,
+	// until the pickaxe can be swung again
+	pub swing_cooldown: f32,
+
+	pub inventory: Inventory,
+	// the chest being opened, and how long interact has been held for it
+	pub opening: Option<(ChestId, f32)>,
+	// the knocked teammate being picked up (an index into State::players), and for how long
+	pub reviving: Option<(usize, f32)>,
+	pub using: Option<Using>,
 }
 
 impl Player {
+	pub fn new(position: Point3<f32>, rot_x: Deg<f32>, team: usize) -> Self {
+		return Self {
+			position,
+			rot_x,
+
+			velocity: Vector3::zero(),
+			grounded: false,
+			ground_normal: Vector3::unit_y(),
+			move_mode: MoveMode::Walking,
+			stance: Stance::Standing,
+			sprinting: false,
+			eye_height: EYE_HEIGHT,
+
+			health: MAX_HEALTH,
+			shield: 0.0,
+			life: Life::Alive,
+			team,
+			last_attacker: None,
+
+			building: None,
+			build_cooldown: 0.0,
+			editing: None,
+			build_material: BuildMaterial::Wood,
+			swing_cooldown: 0.0,
+
+			inventory: Inventory::new(),
+			opening: None,
+			reviving: None,
+			using: None,
+		};
+	}
+
+	// something to shoot with on the warmup island
+	pub fn give_warmup_loadout(&mut self) {
+		for kind in [
+			WeaponKind::AssaultRifle,
+			WeaponKind::Shotgun,
+			WeaponKind::Sniper,
+			WeaponKind::RocketLauncher,
+			WeaponKind::Grenade,
+		] {
+			self.inventory.add(Item::Weapon(Weapon::new(kind, Rarity::Common)));
+		}
+		for (kind, amount) in [(AmmoKind::Medium, 120), (AmmoKind::Shells, 30), (AmmoKind::Heavy, 12), (AmmoKind::Rockets, 6)] {
+			self.inventory.add(Item::Ammo(kind, amount));
+		}
+		return;
+	}
+
 	pub fn sin_cos(&self) -> (f32, f32) {
 		return Rad::from(self.rot_x).0.sin_cos();
 	}
-	
+
 	pub fn forward_right(&self) -> (Vector3<f32>, Vector3<f32>) {
 		let (sin, cos) = self.sin_cos();
 		let forward = Vector3::new(cos, 0.0, sin).normalize();
@@ -19,7 +246,282 @@ impl Player {
 		return (forward, right);
 	}
 
-	pub fn update_pos(&mut self, input: &Input, dt: f32) {
+	pub fn toggle_noclip(&mut self) {
+		self.move_mode = match self.move_mode {
+			MoveMode::Noclip => MoveMode::Walking,
+			_ => MoveMode::Noclip,
+		};
+		self.velocity = Vector3::zero();
+		self.grounded = false;
+		self.stance = Stance::Standing;
+		self.sprinting = false;
+		self.eye_height = EYE_HEIGHT;
+		return;
+	}
+
+	// the bus moves the player around until they jump out
+	pub fn board_bus(&mut self) {
+		self.move_mode = MoveMode::InBus;
+		self.velocity = Vector3::zero();
+		self.grounded = false;
+		self.stance = Stance::Standing;
+		self.sprinting = false;
+		self.eye_height = EYE_HEIGHT;
+		self.building = None;
+		self.editing = None;
+		self.using = None;
+		return;
+	}
+
+	// keeps the bus's speed, but starts falling
+	pub fn leave_bus(&mut self) {
+		if self.move_mode == MoveMode::InBus {
+			self.move_mode = MoveMode::Skydiving;
+			self.velocity.y = 0.0;
+		}
+		return;
+	}
+
+	pub fn deploy_glider(&mut self) {
+		if self.move_mode == MoveMode::Skydiving {
+			self.move_mode = MoveMode::Gliding;
+		}
+		return;
+	}
+
+	// e.g. from explosions. anything upwards lifts the player off the ground.
+	pub fn knock(&mut self, impulse: Vector3<f32>) {
+		if self.move_mode == MoveMode::Noclip {
+			return;
+		}
+		self.velocity += impulse;
+		if impulse.y > 0.0 {
+			self.grounded = false;
+		}
+		return;
+	}
+
+	// returns the new life state if the damage changed it.
+	// players with a teammate still up get knocked instead of eliminated.
+	pub fn take_damage(&mut self, amount: f32, kind: DamageKind, attacker: Option<usize>, can_knock: bool) -> Option<Life> {
+		if attacker.is_some() {
+			self.last_attacker = attacker;
+		}
+		match &mut(self.life) {
+			Life::Eliminated => return None,
+			Life::Knocked { health, .. } => {
+				*health -= amount;
+				if *health > 0.0 {
+					return None;
+				}
+			}
+			Life::Alive => {
+				let mut amount = amount;
+				if kind.hits_shield() {
+					let absorbed = amount.min(self.shield);
+					self.shield -= absorbed;
+					amount -= absorbed;
+				}
+				self.health = (self.health - amount).max(0.0);
+				if self.health > 0.0 {
+					return None;
+				}
+				if can_knock {
+					self.life = Life::Knocked { health: KNOCKED_HEALTH, cause: kind };
+					self.stance = Stance::Crouching;
+					self.building = None;
+					self.editing = None;
+					self.using = None;
+					return Some(self.life);
+				}
+			}
+		};
+		self.eliminate();
+		return Some(self.life);
+	}
+
+	pub fn eliminate(&mut self) {
+		self.life = Life::Eliminated;
+		self.velocity = Vector3::zero();
+		self.building = None;
+		self.editing = None;
+		self.using = None;
+		return;
+	}
+
+	// back up after being knocked, with a little health
+	pub fn revive(&mut self) {
+		if let Life::Knocked { .. } = self.life {
+			self.life = Life::Alive;
+			self.health = REVIVE_HEALTH;
+		}
+		return;
+	}
+
+	// starts channelling the selected consumable, if it would do anything
+	pub fn start_using(&mut self) {
+		let Some(slot) = self.inventory.selected else {
+			return;
+		};
+		let Some(Item::Consumable(kind, _)) = self.inventory.slots[slot] else {
+			return;
+		};
+		let stats = kind.stats();
+		let helps = (stats.health > 0.0 && self.health < stats.health_cap)
+			|| (stats.shield > 0.0 && self.shield < stats.shield_cap);
+		if self.using.is_none() && helps {
+			self.using = Some(Using { slot, kind, elapsed: 0.0 });
+		}
+		return;
+	}
+
+	// finishes the consumable once it's been channelled long enough.
+	// it's cancelled if its slot stopped being selected or no longer has it.
+	pub fn update_using(&mut self, dt: f32) {
+		let Some(using) = &mut(self.using) else {
+			return;
+		};
+		let slot = &mut(self.inventory.slots[using.slot]);
+		let count = match slot {
+			Some(Item::Consumable(kind, count)) if *kind == using.kind && self.inventory.selected == Some(using.slot) => count,
+			_ => {
+				self.using = None;
+				return;
+			}
+		};
+		using.elapsed += dt;
+		let stats = using.kind.stats();
+		if using.elapsed < stats.use_time {
+			return;
+		}
+
+		*count -= 1;
+		if *count == 0 {
+			*slot = None;
+			self.inventory.selected = None;
+		}
+		self.using = None;
+		if self.health < stats.health_cap {
+			self.health = (self.health + stats.health).min(stats.health_cap.min(MAX_HEALTH));
+		}
+		if self.shield < stats.shield_cap {
+			self.shield = (self.shield + stats.shield).min(stats.shield_cap.min(MAX_SHIELD));
+		}
+		return;
+	}
+
+	// picking the piece that's already out puts it away.
+	// also drops any edit in progress.
+	pub fn select_piece(&mut self, kind: PieceKind) {
+		self.editing = None;
+		self.using = None;
+		self.inventory.put_away_selected();
+		self.building = if self.building == Some(kind) { None } else { Some(kind) };
+		return;
+	}
+
+	// moves through the hotbar, putting away any build piece or edit
+	pub fn scroll_slots(&mut self, steps: i32) {
+		self.inventory.scroll(steps);
+		self.building = None;
+		self.editing = None;
+		return;
+	}
+
+	pub fn select_slot(&mut self, index: usize) {
+		self.inventory.select(index);
+		self.building = None;
+		self.editing = None;
+		return;
+	}
+
+	// for being shot: a capsule up to the neck, and a sphere for the head
+	pub fn hitboxes(&self) -> (Capsule, Point3<f32>) {
+		let height = self.height();
+		let body = Capsule::standing(self.position, RADIUS, height - HEAD_RADIUS * 2.0);
+		let head = self.position + Vector3::unit_y() * (height - HEAD_RADIUS);
+		return (body, head);
+	}
+
+	pub fn height(&self) -> f32 {
+		return match self.stance {
+			Stance::Standing => HEIGHT,
+			Stance::Crouching | Stance::Sliding => CROUCH_HEIGHT,
+		};
+	}
+
+	pub fn movement_state(&self) -> MovementState {
+		let moving = Vector3::new(self.velocity.x, 0.0, self.velocity.z).magnitude2() > 0.01;
+		return match (self.move_mode, self.stance) {
+			(MoveMode::Noclip, _) => MovementState::Noclip,
+			(MoveMode::InBus, _) => MovementState::InBus,
+			(MoveMode::Skydiving, _) => MovementState::Skydiving,
+			(MoveMode::Gliding, _) => MovementState::Gliding,
+			(_, Stance::Sliding) => MovementState::Sliding,
+			_ if !self.grounded => MovementState::Airborne,
+			(_, Stance::Crouching) if moving => MovementState::CrouchWalking,
+			(_, Stance::Crouching) => MovementState::CrouchIdle,
+			_ if moving && self.sprinting => MovementState::Sprinting,
+			_ if moving => MovementState::Walking,
+			_ => MovementState::Idle,
+		};
+	}
+
+	// standing back up needs room above a crouched body
+	fn can_stand<G: Ground>(&self, ground: &G) -> bool {
+		let up = Vector3::unit_y() * (HEIGHT - CROUCH_HEIGHT);
+		return ground.sweep_body(self.position, RADIUS, CROUCH_HEIGHT, up).is_none();
+	}
+
+	fn update_stance<G: Ground>(&mut self, input: &Input, ground: &G) {
+		// knocked players stay low
+		if self.life != Life::Alive {
+			self.stance = Stance::Crouching;
+			self.sprinting = false;
+			return;
+		}
+		let horizontal = Vector3::new(self.velocity.x, 0.0, self.velocity.z);
+		let speed = horizontal.magnitude();
+		self.stance = match self.stance {
+			Stance::Standing if input.crouch => {
+				if self.grounded && speed >= SLIDE_MIN_SPEED * input.speed {
+					let boost = horizontal.normalize() * (SLIDE_BOOST * input.speed);
+					self.velocity += boost;
+					Stance::Sliding
+				} else {
+					Stance::Crouching
+				}
+			}
+			Stance::Crouching | Stance::Sliding if !input.crouch && self.can_stand(ground) => Stance::Standing,
+			Stance::Sliding if speed < CROUCH_SPEED * input.speed => Stance::Crouching,
+			stance => stance,
+		};
+
+		// sprinting only makes sense going forwards
+		self.sprinting = input.sprint
+			&& self.using.is_none()
+			&& self.stance == Stance::Standing
+			&& input.amount_forward > input.amount_backward;
+		return;
+	}
+
+	// returns how fast the player was falling if they landed this step
+	pub fn update_pos<G: Ground>(&mut self, input: &Input, ground: &G, dt: f32) -> Option<f32> {
+		if self.life == Life::Eliminated {
+			return None;
+		}
+		return match self.move_mode {
+			MoveMode::Walking => self.walk(input, ground, dt),
+			MoveMode::Noclip => {
+				self.fly(input, dt);
+				None
+			}
+			MoveMode::InBus => None,
+			MoveMode::Skydiving | MoveMode::Gliding => self.fall(input, ground, dt),
+		};
+	}
+
+	fn fly(&mut self, input: &Input, dt: f32) {
 		let (forward, right) = self.forward_right();
 		self.position += forward * (input.amount_forward - input.amount_backward) * (input.speed * dt);
 		self.position += right * (input.amount_right - input.amount_left) * (input.speed * dt);
@@ -28,6 +530,213 @@ impl Player {
 
 		return;
 	}
+
+	fn walk<G: Ground>(&mut self, input: &Input, ground: &G, dt: f32) -> Option<f32> {
+		let (forward, right) = self.forward_right();
+		let mut wish = forward * (input.amount_forward - input.amount_backward)
+			+ right * (input.amount_right - input.amount_left);
+		if wish.magnitude2() > 1.0 {
+			wish = wish.normalize();
+		}
+		self.update_stance(input, ground);
+
+		let mut horizontal = Vector3::new(self.velocity.x, 0.0, self.velocity.z);
+		if self.stance == Stance::Sliding {
+			// keeps its momentum, slowed by friction and sped up going downhill
+			let downhill = Vector3::new(self.ground_normal.x, 0.0, self.ground_normal.z);
+			horizontal += (downhill * GRAVITY + wish * SLIDE_STEER) * dt;
+			let speed = horizontal.magnitude();
+			if speed > 0.0 {
+				horizontal *= (speed - SLIDE_FRICTION * dt).max(0.0) / speed;
+			}
+		} else {
+			// accelerate towards the wanted horizontal velocity
+			let mut max_speed = match self.stance {
+				_ if self.life != Life::Alive => KNOCKED_SPEED,
+				_ if self.sprinting => SPRINT_SPEED,
+				Stance::Crouching => CROUCH_SPEED,
+				_ => WALK_SPEED,
+			};
+			if let Some(using) = &(self.using) {
+				max_speed *= using.kind.stats().move_speed;
+			}
+			let target = wish * (max_speed * input.speed);
+			let accel = if self.grounded { GROUND_ACCEL } else { AIR_ACCEL } * dt;
+			let diff = target - horizontal;
+			horizontal += if diff.magnitude() > accel { diff.normalize() * accel } else { diff };
+		}
+
+		if self.grounded && input.amount_up > 0.0 && self.life == Life::Alive {
+			self.velocity.y = JUMP_SPEED;
+			self.grounded = false;
+			// jumping stops whatever was being used
+			self.using = None;
+			if self.stance == Stance::Sliding {
+				self.stance = if self.can_stand(ground) { Stance::Standing } else { Stance::Crouching };
+			}
+		}
+
+		let target_eye = if self.stance == Stance::Standing { EYE_HEIGHT } else { CROUCH_EYE_HEIGHT };
+		let eye_step = EYE_SPEED * dt;
+		self.eye_height += (target_eye - self.eye_height).clamp(-eye_step, eye_step);
+		if !self.grounded {
+			self.velocity.y -= GRAVITY * dt;
+		}
+
+		return self.move_body(horizontal, ground, dt);
+	}
+
+	// skydiving and gliding: falling at a steady speed while steering, until landing on anything
+	fn fall<G: Ground>(&mut self, input: &Input, ground: &G, dt: f32) -> Option<f32> {
+		let (forward, right) = self.forward_right();
+		let mut wish = forward * (input.amount_forward - input.amount_backward)
+			+ right * (input.amount_right - input.amount_left);
+		if wish.magnitude2() > 1.0 {
+			wish = wish.normalize();
+		}
+		let diving = self.move_mode == MoveMode::Skydiving && input.amount_forward > input.amount_backward;
+		let (fall_speed, max_speed) = match self.move_mode {
+			MoveMode::Gliding => (GLIDE_FALL_SPEED, GLIDE_SPEED),
+			_ if diving => (DIVE_FALL_SPEED, DIVE_SPEED),
+			_ => (SKYDIVE_FALL_SPEED, SKYDIVE_SPEED),
+		};
+
+		let mut horizontal = Vector3::new(self.velocity.x, 0.0, self.velocity.z);
+		let target = wish * (max_speed * input.speed);
+		let accel = FALL_STEER_ACCEL * dt;
+		let diff = target - horizontal;
+		horizontal += if diff.magnitude() > accel { diff.normalize() * accel } else { diff };
+		let accel = if self.velocity.y < -fall_speed { AIR_DRAG } else { GRAVITY } * dt;
+		self.velocity.y += (-fall_speed - self.velocity.y).clamp(-accel, accel);
+
+		if self.move_mode == MoveMode::Skydiving && ground.ground_below(self.position, RADIUS, 0.0, GLIDER_HEIGHT).is_some() {
+			self.deploy_glider();
+		}
+		let landed = self.move_body(horizontal, ground, dt);
+		if landed.is_some() {
+			self.move_mode = MoveMode::Walking;
+		}
+		return landed;
+	}
+
+	// moves by `horizontal` and the vertical velocity, sliding along walls, stepping onto ledges
+	// and landing on whatever's below. returns how fast it was falling if it landed.
+	fn move_body<G: Ground>(&mut self, mut horizontal: Vector3<f32>, ground: &G, dt: f32) -> Option<f32> {
+		let max_slope_cos = Rad::from(MAX_SLOPE).0.cos();
+
+		// horizontal movement. the body is swept with its bottom raised by a step,
+		// so small ledges pass underneath it and get stepped onto below.
+		let lift = if self.grounded { STEP_HEIGHT } else { 0.0 };
+		let before = self.position;
+		let mut body = self.position + Vector3::unit_y() * lift;
+		let mut motion = horizontal * dt;
+		// slide along up to two walls (i.e. into a corner)
+		for _ in 0..3 {
+			match ground.sweep_body(body, RADIUS, self.height() - lift, motion) {
+				Some((time, normal)) => {
+					body += motion * time;
+					let flat = Vector3::new(normal.x, 0.0, normal.z);
+					if flat.magnitude2() < 1e-6 {
+						break;
+					}
+					let flat = flat.normalize();
+					motion = (motion * (1.0 - time)) - flat * (motion * (1.0 - time)).dot(flat);
+					horizontal -= flat * horizontal.dot(flat);
+				}
+				None => {
+					body += motion;
+					break;
+				}
+			}
+		}
+		self.position.x = body.x;
+		self.position.z = body.z;
+
+		// stepped onto something, unless it's too steep to walk up
+		if let Some(hit) = ground.ground_below(self.position, RADIUS, lift, 0.0) {
+			if hit.height > self.position.y {
+				if hit.normal.y < max_slope_cos {
+					self.position = before;
+					horizontal = Vector3::zero();
+				} else {
+					self.position.y = hit.height;
+				}
+			}
+		}
+		self.velocity.x = horizontal.x;
+		self.velocity.z = horizontal.z;
+
+		// vertical movement, landing on anything passed through on the way down
+		let old_y = self.position.y;
+		let new_y = old_y + self.velocity.y * dt;
+		let snap = if self.grounded { GROUND_SNAP } else { 0.0 };
+		let max_down = (old_y - new_y).max(0.0) + snap;
+		let mut landed = None;
+		match ground.ground_below(self.position, RADIUS, 0.0, max_down) {
+			Some(hit) if self.velocity.y <= 0.0 => {
+				self.position.y = hit.height;
+				self.ground_normal = hit.normal;
+				if hit.normal.y >= max_slope_cos {
+					if !self.grounded {
+						landed = Some(-self.velocity.y);
+					}
+					self.grounded = true;
+				} else {
+					// too steep to stand on, slide down it
+					self.grounded = false;
+					let downhill = Vector3::new(hit.normal.x, 0.0, hit.normal.z);
+					self.velocity += downhill * (GRAVITY * dt);
+				}
+				self.velocity.y = 0.0;
+			}
+			_ => {
+				self.position.y = new_y;
+				self.grounded = false;
+				self.ground_normal = Vector3::unit_y();
+			}
+		}
+
+		return landed;
+	}
+
+	// everything a player does from their input except using what they're holding,
+	// the same for people and bots. returns how fast they landed, if they did.
+	pub fn apply_input<G: Ground>(&mut self, input: &mut Input, ground: &G, dt: f32, sf: f32) -> Option<f32> {
+		if std::mem::take(&mut(input.toggle_noclip)) {
+			self.toggle_noclip();
+		}
+		if let Some(kind) = input.select_piece.take() {
+			self.select_piece(kind);
+		}
+		if std::mem::take(&mut(input.next_material)) {
+			self.build_material = self.build_material.next();
+		}
+		if let Some(index) = input.select_slot.take() {
+			self.select_slot(index);
+		}
+		let steps = std::mem::take(&mut(input.scroll_slots));
+		if steps != 0 {
+			self.scroll_slots(steps);
+		}
+		let steps = std::mem::take(&mut(input.move_item));
+		if steps != 0 {
+			self.inventory.move_selected(steps);
+		}
+		if std::mem::take(&mut(input.reload)) {
+			self.inventory.reload();
+		}
+		if std::mem::take(&mut(input.jump)) {
+			match self.move_mode {
+				MoveMode::InBus => self.leave_bus(),
+				MoveMode::Skydiving => self.deploy_glider(),
+				_ => (),
+			};
+		}
+		let landed = self.update_pos(input, ground, dt);
+		self.update_rot(input, sf);
+		return landed;
+	}
+
 	pub fn update_rot(&mut self, input: &Input, sf: f32) {
 		// doesn't need dt because the input is not continuous.
 		let (dx, _) = input.mouse_moved;
@@ -38,8 +747,43 @@ impl Player {
 
 	pub fn adapt_pos_for_camera(&self) -> Point3<f32> {
 		let mut target = self.position;
+		target.y += self.eye_height;
 		target += self.forward_right().1 * (0.0/* player half width */ + 0.0/* additional offset */);
 
 		return target;
 	}
-}
\ No newline at end of file
+}
+
+// the closest knocked teammate of `players[index]` no further than `reach` away
+pub fn nearest_knocked_teammate(players: &[Player], index: usize, reach: f32) -> Option<usize> {
+	let player = &(players[index]);
+	return players
+		.iter()
+		.enumerate()
+		.filter(|&(other, teammate)| other != index && teammate.team == player.team && matches!(teammate.life, Life::Knocked { .. }))
+		.map(|(other, teammate)| (other, (teammate.position - player.position).magnitude()))
+		.filter(|&(_, distance)| distance <= reach)
+		.min_by(|(_, a), (_, b)| a.total_cmp(b))
+		.map(|(other, _)| other);
+}
+
+// damage for landing at `speed`
+pub fn fall_damage(speed: f32) -> f32 {
+	return (speed - SAFE_FALL_SPEED).max(0.0) * FALL_DAMAGE_PER_SPEED;
+}
+
+// boxes standing in for everyone's bodies until there are models, one group each
+// so whoever's looking out from one can leave theirs out.
+// nothing for anyone out or still in the bus.
+pub fn vertices(players: &[Player]) -> Vec<Vec<Vertex>> {
+	return players.iter().map(|player| {
+		if player.life == Life::Eliminated || player.move_mode == MoveMode::InBus {
+			return Vec::new();
+		}
+		return cuboid(player.position, Vector3::new(2.0 * RADIUS, player.height(), 2.0 * RADIUS))
+			.into_iter()
+			.flat_map(|triangle| [triangle.a, triangle.b, triangle.c])
+			.map(|position| Vertex { position: position.into(), tex_coords: [0.0, 0.0] })
+			.collect();
+	}).collect();
+}
@@ -0,0 +1,388 @@
+use cgmath::{Point3, Vector3, InnerSpace, Zero};
+
+use crate::{
+	build::{Buildings, TILE},
+	collision::CollisionWorld,
+	player::{Player, Life},
+	weapon::{self, Weapon, WeaponKind, DamageEvent, DamageKind, DamageTarget},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectileKind {
+	Bullet,
+	Rocket,
+	Grenade,
+}
+
+pub struct Explosion {
+	pub radius: f32,
+	// at the centre, falling off linearly to nothing at the radius
+	pub damage: f32,
+	pub structure_damage: f32,
+	// speed given to players at the centre
+	pub knockback: f32,
+}
+
+pub struct ProjectileStats {
+	pub speed: f32,
+	pub gravity: f32,
+	// fraction of speed lost per second
+	pub drag: f32,
+	// bounces off things keeping this much speed, instead of stopping
+	pub bounciness: Option<f32>,
+	// seconds until it explodes, or just goes away if it doesn't explode
+	pub fuse: f32,
+	pub explosion: Option<Explosion>,
+}
+
+impl ProjectileKind {
+	pub fn stats(self) -> ProjectileStats {
+		return match self {
+			Self::Bullet => ProjectileStats {
+				speed: 250.0, gravity: 6.0, drag: 0.02, bounciness: None, fuse: 3.0, explosion: None,
+			},
+			Self::Rocket => ProjectileStats {
+				speed: 35.0, gravity: 0.0, drag: 0.0, bounciness: None, fuse: 10.0,
+				explosion: Some(Explosion { radius: 4.0, damage: 70.0, structure_damage: 300.0, knockback: 10.0 }),
+			},
+			Self::Grenade => ProjectileStats {
+				speed: 18.0, gravity: 20.0, drag: 0.3, bounciness: Some(0.45), fuse: 2.5,
+				explosion: Some(Explosion { radius: 4.0, damage: 75.0, structure_damage: 200.0, knockback: 8.0 }),
+			},
+		};
+	}
+}
+
+// below this speed a bounce off something flat leaves grenades lying there
+const REST_SPEED: f32 = 1.0;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Projectile {
+	pub kind: ProjectileKind,
+	// what fired it, for the direct hit damage
+	pub weapon: Weapon,
+	// index into the players, never hit by their own projectiles
+	pub owner: usize,
+	pub position: Point3<f32>,
+	pub velocity: Vector3<f32>,
+	pub age: f32,
+	// for damage falloff on direct hits
+	pub travelled: f32,
+	resting: bool,
+}
+
+pub struct Projectiles {
+	pub projectiles: Vec<Projectile>,
+}
+
+impl Projectiles {
+	pub fn new() -> Self {
+		return Self { projectiles: Vec::new() };
+	}
+
+	pub fn spawn(&mut self, kind: ProjectileKind, weapon: Weapon, owner: usize, position: Point3<f32>, dir: Vector3<f32>) {
+		self.projectiles.push(Projectile {
+			kind,
+			weapon,
+			owner,
+			position,
+			velocity: dir.normalize() * kind.stats().speed,
+			age: 0.0,
+			travelled: 0.0,
+			resting: false,
+		});
+		return;
+	}
+
+	// moves everything by one fixed step. returns the damage done, and where things exploded.
+	pub fn update(
+		&mut self,
+		dt: f32,
+		players: &mut [Player],
+		collision: &CollisionWorld,
+		buildings: &Buildings,
+	) -> (Vec<DamageEvent>, Vec<Point3<f32>>) {
+		let mut events = Vec::new();
+		let mut explosions = Vec::new();
+
+		self.projectiles.retain_mut(|projectile| {
+			let stats = projectile.kind.stats();
+			projectile.age += dt;
+
+			let mut finished = false;
+			if !projectile.resting {
+				projectile.velocity.y -= stats.gravity * dt;
+				projectile.velocity *= (-stats.drag * dt).exp();
+
+				let motion = projectile.velocity * dt;
+				let distance = motion.magnitude();
+				let hit = if distance > 0.0 {
+					weapon::trace(projectile.position, motion / distance, distance, Some(projectile.owner), players, collision)
+				} else {
+					None
+				};
+
+				match (hit, stats.bounciness) {
+					(None, _) => {
+						projectile.position += motion;
+						projectile.travelled += distance;
+					}
+					(Some(hit), Some(bounciness)) => {
+						let v = projectile.velocity;
+						projectile.velocity = (v - hit.normal * (2.0 * v.dot(hit.normal))) * bounciness;
+						projectile.position = hit.point + hit.normal * 0.01;
+						if hit.normal.y > 0.7 && projectile.velocity.magnitude() < REST_SPEED {
+							projectile.velocity = Vector3::zero();
+							projectile.resting = true;
+						}
+					}
+					(Some(hit), None) => {
+						// back off a little so explosions start on the near side of walls
+						projectile.position = hit.point + hit.normal * 0.05;
+						projectile.travelled += hit.distance;
+						if stats.explosion.is_none() {
+							if let Some((target, headshot)) = DamageTarget::of(hit.target, buildings) {
+								let amount = projectile.weapon.stats().hit_damage(projectile.travelled, headshot);
+								events.push(DamageEvent {
+									attacker: Some(projectile.owner),
+									target,
+									kind: DamageKind::Weapon(projectile.weapon.kind),
+									amount,
+									headshot,
+									point: hit.point,
+								});
+							}
+						}
+						finished = true;
+					}
+				};
+			}
+
+			if finished || projectile.age >= stats.fuse {
+				if let Some(explosion) = &(stats.explosion) {
+					events.extend(explode(explosion, projectile.weapon.kind, projectile.owner, projectile.position, players, buildings));
+					explosions.push(projectile.position);
+				}
+				return false;
+			}
+			return true;
+		});
+
+		return (events, explosions);
+	}
+}
+
+// area damage to players and pieces, pushing players away from the centre.
+// the owner's caught in it too, apply_damage decides whether that hurts.
+pub fn explode(
+	explosion: &Explosion,
+	weapon: WeaponKind,
+	owner: usize,
+	center: Point3<f32>,
+	players: &mut [Player],
+	buildings: &Buildings,
+) -> Vec<DamageEvent> {
+	let mut events = Vec::new();
+	let falloff = |distance: f32, radius: f32| (1.0 - distance / radius).max(0.0);
+
+	for (index, player) in players.iter_mut().enumerate() {
+		if player.life == Life::Eliminated {
+			continue;
+		}
+		let (body, _) = player.hitboxes();
+		let offset = body.a + (body.b - body.a) * 0.5 - center;
+		let scale = falloff(offset.magnitude(), explosion.radius);
+		if scale <= 0.0 {
+			continue;
+		}
+		events.push(DamageEvent {
+			attacker: Some(owner),
+			target: DamageTarget::Player(index),
+			kind: DamageKind::Explosion(weapon),
+			amount: explosion.damage * scale,
+			headshot: false,
+			point: center,
+		});
+		let away = if offset.magnitude2() > 1e-6 { offset.normalize() } else { Vector3::unit_y() };
+		player.knock((away + Vector3::unit_y() * 0.5) * (explosion.knockback * scale));
+	}
+
+	// pieces are big, so measure to about their nearest edge
+	for (id, piece) in buildings.pieces() {
+		let distance = ((piece.placement.center() - center).magnitude() - TILE * 0.5).max(0.0);
+		let scale = falloff(distance, explosion.radius);
+		if scale > 0.0 {
+			events.push(DamageEvent {
+				attacker: Some(owner),
+				target: DamageTarget::Piece(id),
+				kind: DamageKind::Explosion(weapon),
+				amount: explosion.structure_damage * scale,
+				headshot: false,
+				point: center,
+			});
+		}
+	}
+	return events;
+}
+
+#[cfg(test)]
+mod tests {
+	use cgmath::Deg;
+	use super::*;
+	use crate::{collision::cuboid, loot::Rarity, player};
+
+	const STEP: f32 = 1.0 / 60.0;
+
+	// a big floor with its top at `height`
+	fn world(height: f32) -> CollisionWorld {
+		return CollisionWorld::new(cuboid(Point3::new(0.0, height - 1.0, 0.0), Vector3::new(200.0, 1.0, 200.0)));
+	}
+
+	fn player_at(x: f32, team: usize) -> Player {
+		return Player::new(Point3::new(x, 0.0, 0.0), Deg(0.0), team);
+	}
+
+	fn body_center(player: &Player) -> Point3<f32> {
+		let (body, _) = player.hitboxes();
+		return body.a + (body.b - body.a) * 0.5;
+	}
+
+	fn run(projectiles: &mut Projectiles, seconds: f32, players: &mut [Player], collision: &CollisionWorld) -> (Vec<DamageEvent>, Vec<Point3<f32>>) {
+		let (mut events, mut explosions) = (Vec::new(), Vec::new());
+		for _ in 0..(seconds / STEP).round() as usize {
+			let (more_events, more_explosions) = projectiles.update(STEP, players, collision, &(Buildings::new()));
+			events.extend(more_events);
+			explosions.extend(more_explosions);
+		}
+		return (events, explosions);
+	}
+
+	#[test]
+	fn gravity_and_travel() {
+		let collision = world(-100.0);
+		let weapon = Weapon::new(WeaponKind::RocketLauncher, Rarity::Common);
+		let mut projectiles = Projectiles::new();
+		projectiles.spawn(ProjectileKind::Rocket, weapon, 0, Point3::new(0.0, 10.0, 0.0), Vector3::new(2.0, 0.0, 0.0));
+		projectiles.spawn(ProjectileKind::Grenade, weapon, 0, Point3::new(0.0, 10.0, 0.0), Vector3::unit_x());
+		projectiles.spawn(ProjectileKind::Bullet, weapon, 0, Point3::new(0.0, 10.0, 0.0), Vector3::unit_x());
+		run(&mut(projectiles), 0.5, &mut([]), &(collision));
+
+		// rockets go dead straight at their speed, however long the direction was
+		let rocket = projectiles.projectiles[0];
+		assert!((rocket.position.y - 10.0).abs() < 1e-4);
+		assert!((rocket.position.x - 17.5).abs() < 0.01, "{:?}", rocket.position);
+		assert!((rocket.travelled - 17.5).abs() < 0.01);
+
+		// grenades arc down and slow down
+		let grenade = projectiles.projectiles[1];
+		assert!(grenade.velocity.y < -9.0);
+		assert!(grenade.position.y < 8.0);
+		assert!(grenade.position.x < 18.0 * 0.5);
+
+		// bullets drop a little, and only a little
+		let bullet = projectiles.projectiles[2];
+		let drop = 10.0 - bullet.position.y;
+		assert!(drop > 0.5 && drop < 1.0, "{drop}");
+		assert!(bullet.travelled > bullet.position.x - 1e-3);
+		return;
+	}
+
+	#[test]
+	fn direct_hits() {
+		let collision = world(0.0);
+		let weapon = Weapon::new(WeaponKind::Sniper, Rarity::Common);
+		let mut players = [player_at(0.0, 0), player_at(20.0, 1)];
+		let mut projectiles = Projectiles::new();
+		// fired from inside the owner, who doesn't get hit by it
+		let from = body_center(&(players[0]));
+		projectiles.spawn(ProjectileKind::Bullet, weapon, 0, from, body_center(&(players[1])) - from);
+		let (events, explosions) = run(&mut(projectiles), 0.5, &mut(players), &(collision));
+
+		assert!(projectiles.projectiles.is_empty());
+		assert!(explosions.is_empty());
+		assert_eq!(events.len(), 1);
+		let event = events[0];
+		assert_eq!(event.attacker, Some(0));
+		assert!(matches!(event.target, DamageTarget::Player(1)));
+		assert!(matches!(event.kind, DamageKind::Weapon(WeaponKind::Sniper)));
+		// it stops at the near side of the body
+		assert!((event.point.x - (20.0 - player::RADIUS)).abs() < 0.1, "{:?}", event.point);
+		assert_eq!(event.amount, weapon.stats().hit_damage(event.point.x, false));
+
+		// the ground just stops it
+		projectiles.spawn(ProjectileKind::Bullet, weapon, 0, Point3::new(0.0, 5.0, 0.0), -Vector3::unit_y());
+		let (events, _) = run(&mut(projectiles), 0.5, &mut(players), &(collision));
+		assert!(events.is_empty());
+		assert!(projectiles.projectiles.is_empty());
+		return;
+	}
+
+	#[test]
+	fn rockets_explode_on_impact_and_grenades_bounce() {
+		let collision = world(0.0);
+		let weapon = Weapon::new(WeaponKind::RocketLauncher, Rarity::Common);
+		let mut projectiles = Projectiles::new();
+		projectiles.spawn(ProjectileKind::Rocket, weapon, 0, Point3::new(0.0, 5.0, 0.0), -Vector3::unit_y());
+		let (_, explosions) = run(&mut(projectiles), 0.5, &mut([]), &(collision));
+		assert_eq!(explosions.len(), 1);
+		// backed off the floor a little
+		assert!(explosions[0].y > 0.0 && explosions[0].y < 0.1, "{:?}", explosions[0]);
+
+		projectiles.spawn(ProjectileKind::Grenade, weapon, 0, Point3::new(0.0, 1.0, 0.0), -Vector3::unit_y());
+		let (_, explosions) = run(&mut(projectiles), 2.0, &mut([]), &(collision));
+		assert!(explosions.is_empty());
+		let grenade = projectiles.projectiles[0];
+		assert!(grenade.position.y >= 0.0);
+		assert!(grenade.resting);
+
+		// it goes off when the fuse runs out, wherever it ended up
+		let (_, explosions) = run(&mut(projectiles), 0.6, &mut([]), &(collision));
+		assert_eq!(explosions, vec![grenade.position]);
+		assert!(projectiles.projectiles.is_empty());
+		return;
+	}
+
+	#[test]
+	fn explosions_fall_off() {
+		let explosion = ProjectileKind::Rocket.stats().explosion.unwrap();
+		// the owner's well out of the way
+		let mut players = [player_at(-50.0, 0), player_at(0.0, 1), player_at(1.0, 2), player_at(3.0, 3), player_at(5.0, 4)];
+		let center = body_center(&(players[1]));
+		let events = explode(&(explosion), WeaponKind::RocketLauncher, 0, center, &mut(players), &(Buildings::new()));
+
+		let damage = |index: usize| events
+			.iter()
+			.find(|event| matches!(event.target, DamageTarget::Player(target) if target == index))
+			.map(|event| event.amount);
+		assert_eq!(damage(1), Some(explosion.damage));
+		assert!((damage(2).unwrap() - explosion.damage * 0.75).abs() < 1e-3);
+		assert!((damage(3).unwrap() - explosion.damage * 0.25).abs() < 1e-3);
+		assert_eq!(damage(4), None);
+		assert!(events.iter().all(|event| event.attacker == Some(0) && matches!(event.kind, DamageKind::Explosion(WeaponKind::RocketLauncher))));
+
+		// pushed away from the centre and up, harder the closer they were
+		assert!(players[2].velocity.x > players[3].velocity.x);
+		assert!(players[3].velocity.x > 0.0 && players[3].velocity.y > 0.0);
+		assert_eq!(players[4].velocity, Vector3::zero());
+		// right on top of it, straight up
+		assert!(players[1].velocity.x.abs() < 1e-6 && players[1].velocity.y > 0.0);
+		return;
+	}
+
+	#[test]
+	fn owners_are_caught_in_their_own_explosions() {
+		let collision = world(0.0);
+		let weapon = Weapon::new(WeaponKind::RocketLauncher, Rarity::Common);
+		let mut players = [player_at(0.0, 0)];
+		let mut projectiles = Projectiles::new();
+		projectiles.spawn(ProjectileKind::Rocket, weapon, 0, body_center(&(players[0])), -Vector3::unit_y());
+		let (events, explosions) = run(&mut(projectiles), 0.5, &mut(players), &(collision));
+
+		assert_eq!(explosions.len(), 1);
+		let event = events.iter().find(|event| matches!(event.target, DamageTarget::Player(0))).unwrap();
+		assert_eq!(event.attacker, Some(0));
+		assert!(event.amount > 0.0);
+		assert!(players[0].velocity.y > 0.0);
+		return;
+	}
+}
@@ -0,0 +1,110 @@
+use cgmath::{Point3, Vector3};
+
+use crate::{
+	build::BuildMaterial,
+	collision::{CollisionWorld, ColliderId, cuboid},
+	obj::Vertex,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropKind {
+	Tree,
+	Rock,
+	Car,
+}
+
+pub struct PropStats {
+	pub health: f32,
+	pub material: BuildMaterial,
+	// given on every hit, not just the last
+	pub yield_per_hit: u32,
+	// width, height, depth
+	pub size: Vector3<f32>,
+}
+
+impl PropKind {
+	pub fn stats(self) -> PropStats {
+		return match self {
+			Self::Tree => PropStats { health: 150.0, material: BuildMaterial::Wood, yield_per_hit: 6, size: Vector3::new(0.8, 5.0, 0.8) },
+			Self::Rock => PropStats { health: 300.0, material: BuildMaterial::Brick, yield_per_hit: 8, size: Vector3::new(2.0, 1.5, 2.0) },
+			Self::Car => PropStats { health: 400.0, material: BuildMaterial::Metal, yield_per_hit: 10, size: Vector3::new(1.8, 1.4, 4.0) },
+		};
+	}
+}
+
+// something in the world that can be harvested for materials
+#[derive(Debug, Clone, Copy)]
+pub struct Prop {
+	pub kind: PropKind,
+	// centre of its base
+	pub position: Point3<f32>,
+	pub health: f32,
+	pub collider: ColliderId,
+}
+
+pub type PropId = usize;
+
+pub struct Props {
+	props: Vec<Option<Prop>>,
+	// bumped on every change, see Buildings
+	generation: u64,
+}
+
+impl Props {
+	pub fn new() -> Self {
+		return Self { props: Vec::new(), generation: 0 };
+	}
+
+	pub fn generation(&self) -> u64 {
+		return self.generation;
+	}
+
+	pub fn spawn(&mut self, kind: PropKind, position: Point3<f32>, collision: &mut CollisionWorld) -> PropId {
+		let collider = collision.add_dynamic(cuboid(position, kind.stats().size));
+		self.props.push(Some(Prop { kind, position, health: kind.stats().health, collider }));
+		self.generation += 1;
+		return self.props.len() - 1;
+	}
+
+	pub fn clear(&mut self, collision: &mut CollisionWorld) {
+		for prop in self.props.drain(..).flatten() {
+			collision.remove_dynamic(prop.collider);
+		}
+		self.generation += 1;
+		return;
+	}
+
+	pub fn with_collider(&self, collider: ColliderId) -> Option<PropId> {
+		return self.props
+			.iter()
+			.position(|prop| prop.is_some_and(|prop| prop.collider == collider));
+	}
+
+	// returns what the hit gave, the prop goes away once it runs out of health
+	pub fn hit(&mut self, id: PropId, damage: f32, collision: &mut CollisionWorld) -> Option<(BuildMaterial, u32)> {
+		let slot = self.props.get_mut(id)?;
+		let prop = slot.as_mut()?;
+		let stats = prop.kind.stats();
+		prop.health -= damage;
+		if prop.health <= 0.0 {
+			collision.remove_dynamic(prop.collider);
+			*slot = None;
+			self.generation += 1;
+		}
+		return Some((stats.material, stats.yield_per_hit));
+	}
+
+	// grouped by the material they give
+	pub fn vertices(&self) -> [Vec<Vertex>; 3] {
+		let mut vertices = [Vec::new(), Vec::new(), Vec::new()];
+		for prop in self.props.iter().flatten() {
+			let group = &mut(vertices[prop.kind.stats().material.index()]);
+			for triangle in cuboid(prop.position, prop.kind.stats().size) {
+				for position in [triangle.a, triangle.b, triangle.c] {
+					group.push(Vertex { position: position.into(), tex_coords: [0.0, 0.0] });
+				}
+			}
+		}
+		return vertices;
+	}
+}
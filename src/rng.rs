@@ -0,0 +1,48 @@
+// splitmix64: tiny, fast, and gives the same numbers everywhere,
+// for anything that has to come out the same on every machine
+// streams for everything random about a match (see Rng::stream), each with the
+// low 32 bits left for telling its own apart, e.g. by spawn point or chest
+pub const LOOT_FLOOR: u64 = 0;
+pub const LOOT_CHEST: u64 = 1 << 32;
+pub const STORM: u64 = 2 << 32;
+pub const BUS: u64 = 3 << 32;
+pub const BOT: u64 = 4 << 32;
+// the next match's seed
+pub const MATCH: u64 = 5 << 32;
+
+#[derive(Debug, Clone)]
+pub struct Rng {
+	state: u64,
+}
+
+impl Rng {
+	pub fn new(seed: u64) -> Self {
+		return Self { state: seed };
+	}
+
+	// one of many independent sequences from the same seed, e.g. one per loot spawn
+	// so what each gives doesn't depend on the order they're rolled in
+	pub fn stream(seed: u64, stream: u64) -> Self {
+		let mut rng = Self::new(seed ^ stream.wrapping_mul(0xd1b54a32d192ed03));
+		rng.next_u64();
+		return rng;
+	}
+
+	pub fn next_u64(&mut self) -> u64 {
+		self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+		let mut z = self.state;
+		z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+		z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+		return z ^ (z >> 31);
+	}
+
+	// 0..1
+	pub fn next_f32(&mut self) -> f32 {
+		// top 24 bits, exactly representable
+		return (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32;
+	}
+
+	pub fn range(&mut self, min: f32, max: f32) -> f32 {
+		return min + (max - min) * self.next_f32();
+	}
+}
@@ -0,0 +1,166 @@
+use cgmath::Deg;
+use crate::{
+	camera::{DepthMode, Fov},
+	match_controller::TeamMode,
+	bot::BotSkill,
+};
+
+#[derive(Debug, Clone)]
+pub struct Settings {
+	pub depth_mode: DepthMode,
+
+	pub fov: Fov,
+	// magnification while aiming down sights
+	pub ads_zoom: f32,
+	// seconds to go from hip to fully zoomed
+	pub ads_time: f32,
+	// see Camera::sensitivity_scale
+	pub sens_match: f32,
+	// recoil permanently moves the aim instead of recovering
+	pub recoil_moves_aim: bool,
+
+	// sprint/crouch flip on each press instead of being held
+	pub sprint_toggle: bool,
+	pub crouch_toggle: bool,
+
+	// split-screen players sharing this window
+	pub local_players: usize,
+
+	// the same seed always gives the same loot, random if None
+	pub seed: Option<u64>,
+	pub team_mode: TeamMode,
+	// whether teammates (and the player themselves) can be hurt
+	pub friendly_fire: bool,
+
+	// computer players added after the local ones
+	pub bots: usize,
+	pub bot_skill: BotSkill,
+
+	// prints the match, storm and every local player's state along with the fps
+	pub debug_stats: bool,
+}
+
+impl Default for Settings {
+	fn default() -> Self {
+		return Self {
+			depth_mode: DepthMode::ReverseZ,
+
+			fov: Fov::Vertical(Deg(40.0)),
+			ads_zoom: 1.5,
+			ads_time: 0.15,
+			sens_match: 0.0,
+			recoil_moves_aim: false,
+
+			sprint_toggle: false,
+			crouch_toggle: false,
+
+			local_players: 1,
+
+			seed: None,
+			team_mode: TeamMode::Solo,
+			friendly_fire: false,
+
+			bots: 0,
+			bot_skill: BotSkill::default(),
+
+			debug_stats: false,
+		};
+	}
+}
+
+fn env_f32(name: &str, err: &'static str) -> Result<Option<f32>, &'static str> {
+	return match std::env::var(name) {
+		Ok(value) => value.parse().map(Some).map_err(|_| err),
+		Err(_) => Ok(None),
+	};
+}
+
+impl Settings {
+	// GAME_DEPTH=standard|reverse_z
+	// GAME_VFOV / GAME_HFOV=degrees, GAME_ADS_ZOOM, GAME_ADS_TIME, GAME_SENS_MATCH
+	// GAME_RECOIL_MOVES_AIM=0|1
+	// GAME_SPRINT_TOGGLE / GAME_CROUCH_TOGGLE=0|1
+	// GAME_LOCAL_PLAYERS=count
+	// GAME_SEED=number
+	// GAME_TEAMS=solo|duos|squads
+	// GAME_FRIENDLY_FIRE=0|1
+	// GAME_BOTS=count, GAME_BOT_REACTION=seconds, GAME_BOT_AIM_ERROR=degrees
+	pub fn from_env() -> Result<Self, &'static str> {
+		let mut settings = Self::default();
+		if let Ok(depth) = std::env::var("GAME_DEPTH") {
+			settings.depth_mode = match depth.as_str() {
+				"standard" => DepthMode::Standard,
+				"reverse_z" => DepthMode::ReverseZ,
+				_ => return Err("GAME_DEPTH must be standard or reverse_z"),
+			};
+		}
+
+		if let Some(fovy) = env_f32("GAME_VFOV", "GAME_VFOV must be a number")? {
+			if !(fovy > 0.0 && fovy < 180.0) {
+				return Err("GAME_VFOV must be between 0 and 180");
+			}
+			settings.fov = Fov::Vertical(Deg(fovy));
+		}
+		if let Some(fovx) = env_f32("GAME_HFOV", "GAME_HFOV must be a number")? {
+			if !(fovx > 0.0 && fovx < 180.0) {
+				return Err("GAME_HFOV must be between 0 and 180");
+			}
+			settings.fov = Fov::Horizontal(Deg(fovx));
+		}
+		if let Some(zoom) = env_f32("GAME_ADS_ZOOM", "GAME_ADS_ZOOM must be a number")? {
+			// anything else divides by zero or flips the view in Camera::fovy
+			if zoom.is_nan() || zoom <= 0.0 {
+				return Err("GAME_ADS_ZOOM must be more than 0");
+			}
+			settings.ads_zoom = zoom;
+		}
+		if let Some(time) = env_f32("GAME_ADS_TIME", "GAME_ADS_TIME must be a number")? {
+			settings.ads_time = time;
+		}
+		if let Some(sens_match) = env_f32("GAME_SENS_MATCH", "GAME_SENS_MATCH must be a number")? {
+			settings.sens_match = sens_match;
+		}
+		if let Ok(value) = std::env::var("GAME_RECOIL_MOVES_AIM") {
+			settings.recoil_moves_aim = value == "1";
+		}
+		if let Ok(value) = std::env::var("GAME_SPRINT_TOGGLE") {
+			settings.sprint_toggle = value == "1";
+		}
+		if let Ok(value) = std::env::var("GAME_CROUCH_TOGGLE") {
+			settings.crouch_toggle = value == "1";
+		}
+		if let Ok(value) = std::env::var("GAME_LOCAL_PLAYERS") {
+			settings.local_players = value.parse().map_err(|_| "GAME_LOCAL_PLAYERS must be a number")?;
+			if settings.local_players == 0 {
+				return Err("GAME_LOCAL_PLAYERS must be at least 1");
+			}
+		}
+		if let Ok(value) = std::env::var("GAME_SEED") {
+			settings.seed = Some(value.parse().map_err(|_| "GAME_SEED must be a number")?);
+		}
+		if let Ok(teams) = std::env::var("GAME_TEAMS") {
+			settings.team_mode = match teams.as_str() {
+				"solo" => TeamMode::Solo,
+				"duos" => TeamMode::Duos,
+				"squads" => TeamMode::Squads,
+				_ => return Err("GAME_TEAMS must be solo, duos or squads"),
+			};
+		}
+		if let Ok(value) = std::env::var("GAME_FRIENDLY_FIRE") {
+			settings.friendly_fire = value == "1";
+		}
+		if let Ok(value) = std::env::var("GAME_BOTS") {
+			settings.bots = value.parse().map_err(|_| "GAME_BOTS must be a number")?;
+		}
+		if let Some(reaction) = env_f32("GAME_BOT_REACTION", "GAME_BOT_REACTION must be a number")? {
+			settings.bot_skill.reaction_time = reaction;
+		}
+		if let Some(error) = env_f32("GAME_BOT_AIM_ERROR", "GAME_BOT_AIM_ERROR must be a number")? {
+			settings.bot_skill.aim_error = error;
+		}
+		if let Ok(value) = std::env::var("GAME_DEBUG_STATS") {
+			settings.debug_stats = value == "1";
+		}
+		return Ok(settings);
+	}
+}
@@ -1,11 +1,33 @@
-use cgmath::{Deg, Rad, Point3};
+use cgmath::{Deg, Rad, Point2, Point3, Vector2, Vector3, InnerSpace};
+use wgpu::util::DeviceExt;
 use winit::window::Window;
 
-use crate::{camera::*, Input, player::Player, obj::{self, Vertex}};
+use crate::{
+	camera::DepthMode,
+	camera_path::*,
+	input::{Input, Bindings},
+	local_player::{LocalPlayer, Seat, Viewport, Ghost},
+	bot::{Bot, BotView},
+	navmesh::NavMesh,
+	player::{self, Player, Life, MoveMode, EliminationEvent, BLEED_RATE},
+	build::{Buildings, Piece, Placement, EditSession, EditPattern, TILE, PIECE_COST},
+	prop::{Props, PropKind},
+	pickup::{self, Pickups},
+	chest::{self, Chests},
+	loot::LootTables,
+	storm::{self, Storm, StormPhase, StormUniform, Circle},
+	bus::Bus,
+	match_controller::{MatchController, MatchPhase, MatchEvent},
+	rng::{self, Rng},
+	weapon::{self, DamageEvent, DamageKind, DamageTarget},
+	projectile::Projectiles,
+	collision::{CollisionWorld, Triangle},
+	obj::{self, Vertex},
+	texture::Texture,
+	settings::Settings,
+};
 
 pub struct State {
-	pub input: Input,
-
 	window: Window,
 	size: winit::dpi::PhysicalSize<u32>,
 	fullscreen: bool,
@@ -16,15 +38,194 @@ pub struct State {
 	queue: wgpu::Queue,
 	config: wgpu::SurfaceConfiguration,
 	render_pipeline: wgpu::RenderPipeline,
+	// alpha blended and without depth writes, for build previews.
+	// passes on equal depth so edit highlights can sit exactly on their piece.
+	ghost_pipeline: wgpu::RenderPipeline,
+	// the storm wall, see storm.wgsl
+	storm_pipeline: wgpu::RenderPipeline,
 
-	player: Player,
-	pub camera: Camera,
-	camera_uniform: CameraUniform,
-	camera_bind_group: wgpu::BindGroup,
+	pub players: Vec<Player>,
+	pub collision: CollisionWorld,
+	pub buildings: Buildings,
+	pub props: Props,
+	pub projectiles: Projectiles,
+	pub pickups: Pickups,
+	pub chests: Chests,
+	loot: LootTables,
+	pub storm: Storm,
+	pub bus: Bus,
+	pub match_controller: MatchController,
+	// everything random about the match comes from this
+	pub seed: u64,
+	// whether teammates (and the player themselves) can be hurt
+	friendly_fire: bool,
+	// everything that took damage in the last update
+	pub damage_events: Vec<DamageEvent>,
+	// every elimination so far, in order
+	pub eliminations: Vec<EliminationEvent>,
+	pub local_players: Vec<LocalPlayer>,
+	pub bots: Vec<Bot>,
+	// where bots can walk, worked out once the map's been put together for the match
+	// and patched up wherever anything's built or broken after that
+	navmesh: NavMesh,
 
 	depth_view: wgpu::TextureView,
+	depth_mode: DepthMode,
 
 	skin: obj::Model,
+
+	// placed pieces and props, with one material for each of wood, brick and metal
+	build_buffer: GroupedBuffer,
+	build_materials: [obj::Material; 3],
+	prop_buffer: GroupedBuffer,
+	prop_materials: [obj::Material; 3],
+	// dropped materials, ammo, consumables, then weapons by rarity
+	pickup_buffer: GroupedBuffer,
+	pickup_materials: [obj::Material; pickup::GROUPS],
+	// closed, then opened
+	chest_buffer: GroupedBuffer,
+	chest_materials: [obj::Material; 2],
+	bus_buffer: GroupedBuffer,
+	bus_materials: [obj::Material; 1],
+	// one group per player, drawn up or knocked
+	player_buffer: GroupedBuffer,
+	player_materials: [obj::Material; 2],
+	// bumped every frame, since someone's always moving
+	player_generation: u64,
+	// valid and invalid placement
+	ghost_materials: [obj::Material; 2],
+	storm_uniform: StormUniform,
+	storm_bind_group: wgpu::BindGroup,
+	storm_buffer: wgpu::Buffer,
+	storm_vertices: u32,
+	// everything drawn before the storm, for it to bend what's behind it
+	scene_copy: obj::Material,
+	texture_bind_group_layout: wgpu::BindGroupLayout,
+}
+
+// seconds between pieces while fire is held
+const BUILD_INTERVAL: f32 = 0.15;
+// how far away a piece can be to start editing it
+const EDIT_REACH: f32 = 2.0 * TILE;
+// pieces falling further away than this don't shake the camera
+const COLLAPSE_SHAKE_RANGE: f32 = 4.0 * TILE;
+const SWING_INTERVAL: f32 = 0.5;
+const PICKAXE_REACH: f32 = 2.5;
+const PICKAXE_DAMAGE: f32 = 50.0;
+// explosions further away than this don't shake the camera
+const EXPLOSION_SHAKE_RANGE: f32 = 20.0;
+// how close something on the ground has to be to pick it up
+const PICKUP_REACH: f32 = 2.0;
+// how close a knocked teammate has to be to revive them
+const REVIVE_REACH: f32 = 1.5;
+// how far in front of the player dropped items land
+const DROP_DISTANCE: f32 = 1.0;
+const LOOT_TABLES: &str = "loot.txt";
+const STORM_PHASES: &str = "storm.txt";
+// the first circle, covering everything there is so far
+const STORM_START: Circle = Circle { center: Point2::new(0.0, 0.0), radius: 80.0 };
+// well out of the way of the actual map
+const WARMUP_ISLAND: Point3<f32> = Point3::new(0.0, 0.0, -200.0);
+// the size of each square bots find their way around in
+const NAVMESH_CELL: f32 = 1.0;
+
+// what everyone's tools did this update, dealt with once they've all had their go
+#[derive(Default)]
+struct ToolEffects {
+	destroyed: Vec<Piece>,
+	// knocked players whose revive just finished
+	revived: Vec<usize>,
+}
+
+struct View<'a> {
+	viewport: Viewport,
+	// whose eyes it's from, so they don't see the inside of their own body
+	player: Option<usize>,
+	camera_bind_group: &'a wgpu::BindGroup,
+	ghost: Option<(&'a wgpu::Buffer, u32, bool)>,
+}
+
+impl<'a> View<'a> {
+	fn apply(&self, render_pass: &mut wgpu::RenderPass<'a>) {
+		let viewport = self.viewport;
+		render_pass.set_viewport(
+			viewport.x as f32, viewport.y as f32,
+			viewport.width as f32, viewport.height as f32,
+			0.0, 1.0,
+		);
+		render_pass.set_scissor_rect(viewport.x, viewport.y, viewport.width, viewport.height);
+
+		// camera
+		render_pass.set_bind_group(0, self.camera_bind_group, &[]);
+		return;
+	}
+}
+
+// a vertex buffer holding several groups that are drawn with different materials,
+// rewritten whenever whatever it's drawing changes
+struct GroupedBuffer {
+	label: &'static str,
+	buffer: wgpu::Buffer,
+	capacity: usize,
+	ranges: Vec<std::ops::Range<u32>>,
+	generation: u64,
+}
+
+impl GroupedBuffer {
+	fn new(device: &wgpu::Device, label: &'static str) -> Self {
+		let capacity = 256;
+		return Self {
+			label,
+			buffer: Self::create(device, label, capacity),
+			capacity,
+			ranges: Vec::new(),
+			generation: 0,
+		};
+	}
+
+	fn create(device: &wgpu::Device, label: &'static str, capacity: usize) -> wgpu::Buffer {
+		return device.create_buffer(&(wgpu::BufferDescriptor {
+			label: Some(label),
+			usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+			size: (std::mem::size_of::<Vertex>() * capacity) as u64,
+			mapped_at_creation: false,
+		}));
+	}
+
+	fn update<G: AsRef<[Vec<Vertex>]>, F: FnOnce() -> G>(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, generation: u64, groups: F) {
+		if self.generation == generation {
+			return;
+		}
+		let groups = groups();
+		let groups = groups.as_ref();
+		let vertices = groups.concat();
+		if vertices.len() > self.capacity {
+			self.capacity = vertices.len().next_power_of_two();
+			self.buffer = Self::create(device, self.label, self.capacity);
+		}
+		queue.write_buffer(&(self.buffer), 0, bytemuck::cast_slice(&(vertices)));
+
+		self.ranges.clear();
+		let mut start = 0;
+		for group in groups {
+			let end = start + group.len() as u32;
+			self.ranges.push(start..end);
+			start = end;
+		}
+		self.generation = generation;
+		return;
+	}
+
+	fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, materials: &'a [obj::Material]) {
+		render_pass.set_vertex_buffer(0, self.buffer.slice(..));
+		for (range, material) in self.ranges.iter().zip(materials) {
+			if !range.is_empty() {
+				render_pass.set_bind_group(1, &(material.bind_group), &[]);
+				render_pass.draw(range.clone(), 0..1);
+			}
+		}
+		return;
+	}
 }
 
 fn depth_view(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) -> wgpu::TextureView {
@@ -46,9 +247,27 @@ fn depth_view(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) -> wgp
 	return texture.create_view(&(wgpu::TextureViewDescriptor::default()));
 }
 
+// for landing too hard, if they did
+fn fall_damage_event(index: usize, player: &Player, speed: f32) -> Option<DamageEvent> {
+	let amount = player::fall_damage(speed);
+	if amount <= 0.0 {
+		return None;
+	}
+	return Some(DamageEvent {
+		attacker: None,
+		target: DamageTarget::Player(index),
+		kind: DamageKind::Fall,
+		amount,
+		headshot: false,
+		point: player.position,
+	});
+}
+
 impl State {
-	pub fn new(window: Window, input: Input) -> Result<Self, &'static str> {
+	pub fn new(window: Window, mut input: Input, settings: Settings) -> Result<Self, &'static str> {
 		let size = window.inner_size();
+		input.sprint_toggle = settings.sprint_toggle;
+		input.crouch_toggle = settings.crouch_toggle;
 
 		let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
 			backends: wgpu::Backends::all(),
@@ -80,7 +299,8 @@ impl State {
 			let caps = surface.get_capabilities(&(adapter));
 			println!("{caps:?}");
 			wgpu::SurfaceConfiguration {
-				usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+				// copied from partway through drawing, for the storm
+				usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
 				format: caps
 					.formats
 					.into_iter()
@@ -97,34 +317,12 @@ impl State {
 
 		let shader = device.create_shader_module(wgpu::include_wgsl!("shader.wgsl"));
 
-		/*let plane_buffer = device.create_buffer_init(
-			&(wgpu::util::BufferInitDescriptor {
-				label: Some("plane_buffer"),
-				usage: wgpu::BufferUsages::VERTEX,
-				contents: bytemuck::cast_slice(&[
-					Vertex { position: [0.0, 0.0, 0.0], color: [0.0, 1.0, 1.0] }, // top
-					Vertex { position: [5.0, 0.0, -5.0], color: [0.0, 1.0, 0.0] }, // left
-					Vertex { position: [0.0, 0.0, -5.0], color: [0.0, 1.0, 0.0] }, // right
-
-					Vertex { position: [0.0, 0.0, 0.0], color: [0.0, 1.0, 0.0] }, // top
-					Vertex { position: [5.0, 0.0, -0.0], color: [0.0, 1.0, 0.0] }, // left
-					Vertex { position: [5.0, 0.0, -5.0], color: [0.0, 1.0, 0.0] }, // right
-				]),
-			})
-		);
-		let build_buffer = device.create_buffer(&(wgpu::BufferDescriptor {
-			label: Some("build_buffer"),
-			usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
-			size: std::mem::size_of::<Vertex>() as u64 * 6,
-			mapped_at_creation: false,
-		}));*/
-
-		let player = Player {
-			position: (-0.275, 1.25, -1.0).into(),
-			rot_x: Deg(90.0),
-		};
-		let mut camera = Camera::new(size);
-		camera.set_pos(player.adapt_pos_for_camera());
+		let build_buffer = GroupedBuffer::new(&(device), "build_buffer");
+		let prop_buffer = GroupedBuffer::new(&(device), "prop_buffer");
+		let pickup_buffer = GroupedBuffer::new(&(device), "pickup_buffer");
+		let chest_buffer = GroupedBuffer::new(&(device), "chest_buffer");
+		let bus_buffer = GroupedBuffer::new(&(device), "bus_buffer");
+		let player_buffer = GroupedBuffer::new(&(device), "player_buffer");
 
 		let camera_bind_group_layout = &(device.create_bind_group_layout(&(wgpu::BindGroupLayoutDescriptor {
 			entries: &[
@@ -144,21 +342,21 @@ impl State {
 
 		let depth_view = depth_view(&(device), &(config));
 
-		let camera_uniform = CameraUniform::new(&(device));
-		camera_uniform.set_view_projection_matrix(&(queue), &(camera));
-
-		let camera_bind_group = device.create_bind_group(&(wgpu::BindGroupDescriptor {
-			layout: camera_bind_group_layout,
-			entries: &[
-				wgpu::BindGroupEntry {
-					binding: 0,
-					resource: camera_uniform.as_entire_binding(),
-				}
-			],
-			label: Some("camera_bind_group"),
-		}));
+		let mut players = Vec::new();
+		let mut local_players = Vec::new();
+		for index in 0..settings.local_players {
+			let player = Player::new((-0.275 + index as f32, 0.0, -1.0).into(), Deg(90.0), settings.team_mode.team(index));
+			let seat = Seat {
+				input: input.clone(),
+				bindings: Bindings::for_local_player(index),
+				player: players.len(),
+				viewport: Viewport::split(size, settings.local_players, index),
+			};
+			local_players.push(LocalPlayer::new(&(device), camera_bind_group_layout, &(settings), seat, &(player)));
+			players.push(player);
+		}
 
-		let texture_bind_group_layout = &(device.create_bind_group_layout(&(wgpu::BindGroupLayoutDescriptor {
+		let texture_bind_group_layout = device.create_bind_group_layout(&(wgpu::BindGroupLayoutDescriptor {
 			entries: &[
 				wgpu::BindGroupLayoutEntry {
 					binding: 0,
@@ -178,17 +376,77 @@ impl State {
 				},
 			],
 			label: Some("texture_bind_group_layout"),
-		})));
+		}));
 		
 		let skin = obj::load_obj("models/skin.obj", &(device), &(queue), &(texture_bind_group_layout));
+		let solid = |rgba, label| obj::Material::solid(&(device), &(queue), &(texture_bind_group_layout), rgba, Some(label));
+		// wood, brick, metal
+		let build_materials = [
+			solid(0xb08850ff, "wood_piece"),
+			solid(0xa05040ff, "brick_piece"),
+			solid(0x8890a0ff, "metal_piece"),
+		];
+		let prop_materials = [
+			solid(0x406030ff, "tree"),
+			solid(0x707070ff, "rock"),
+			solid(0x3050a0ff, "car"),
+		];
+		let pickup_materials = [
+			solid(0xd0a870ff, "wood_pickup"),
+			solid(0xc07060ff, "brick_pickup"),
+			solid(0xa8b0c0ff, "metal_pickup"),
+			solid(0x808040ff, "ammo_pickup"),
+			solid(0xe0e0e0ff, "consumable_pickup"),
+			solid(0x909090ff, "common_pickup"),
+			solid(0x40b040ff, "uncommon_pickup"),
+			solid(0x3070e0ff, "rare_pickup"),
+			solid(0xa040d0ff, "epic_pickup"),
+			solid(0xe09020ff, "legendary_pickup"),
+		];
+		let chest_materials = [
+			solid(0xc09030ff, "chest"),
+			solid(0x604818ff, "opened_chest"),
+		];
+		let bus_materials = [solid(0x3060c0ff, "bus")];
+		let player_materials = [
+			solid(0xe0c090ff, "player"),
+			solid(0xc04040ff, "knocked_player"),
+		];
+		let ghost_materials = [
+			solid(0x40a0ff60, "ghost_valid"),
+			solid(0xff404060, "ghost_invalid"),
+		];
+
+		// a big floor until there's an actual map
+		const FLOOR: f32 = 1000.0;
+		let static_triangles = vec![
+			Triangle::new((-FLOOR, 0.0, -FLOOR).into(), (-FLOOR, 0.0, FLOOR).into(), (FLOOR, 0.0, FLOOR).into()),
+			Triangle::new((FLOOR, 0.0, FLOOR).into(), (FLOOR, 0.0, -FLOOR).into(), (-FLOOR, 0.0, -FLOOR).into()),
+		];
+		let collision = CollisionWorld::new(static_triangles);
+
+		// props are put out for each match, see spawn_props,
+		// and loot once the bus leaves, see spawn_loot
+		let seed = settings.seed.unwrap_or_else(|| {
+			std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |time| time.as_nanos() as u64)
+		});
+		let mut bots = Vec::new();
+		for index in 0..settings.bots {
+			let team = settings.team_mode.team(players.len());
+			players.push(Player::new((-0.275 + (settings.local_players + index) as f32, 0.0, -1.0).into(), Deg(90.0), team));
+			bots.push(Bot::new(players.len() - 1, settings.bot_skill, seed));
+		}
+		let loot = LootTables::load(LOOT_TABLES)?;
+		let storm = Storm::new(StormPhase::load(STORM_PHASES)?, STORM_START, seed);
+		let bus = Bus::new(&(STORM_START), seed);
 
 		let render_pipeline_layout = device.create_pipeline_layout(&(wgpu::PipelineLayoutDescriptor {
 			label: Some("render_pipeline_layout"),
-			bind_group_layouts: &[camera_bind_group_layout, texture_bind_group_layout],
+			bind_group_layouts: &[camera_bind_group_layout, &(texture_bind_group_layout)],
 			push_constant_ranges: &[],
 		}));
-		let render_pipeline = device.create_render_pipeline(&(wgpu::RenderPipelineDescriptor {
-			label: Some("render_pipeline"),
+		let create_pipeline = |label, blend, depth_write_enabled, depth_compare| device.create_render_pipeline(&(wgpu::RenderPipelineDescriptor {
+			label: Some(label),
 			layout: Some(&(render_pipeline_layout)),
 			vertex: wgpu::VertexState {
 				module: &(shader),
@@ -207,7 +465,7 @@ impl State {
 				entry_point: "fs_main",
 				targets: &[Some(wgpu::ColorTargetState {
 					format: config.format,
-					blend: Some(wgpu::BlendState::REPLACE),
+					blend: Some(blend),
 					write_mask: wgpu::ColorWrites::ALL,
 				})],
 			}),
@@ -223,8 +481,97 @@ impl State {
 			},
 			depth_stencil: Some(wgpu::DepthStencilState {
 				format: wgpu::TextureFormat::Depth32Float,
-				depth_write_enabled: true,
-				depth_compare: wgpu::CompareFunction::Less,
+				depth_write_enabled,
+				depth_compare,
+				stencil: wgpu::StencilState::default(),
+				bias: wgpu::DepthBiasState::default(),
+			}),
+			multisample: wgpu::MultisampleState {
+				count: 1,
+				mask: !0,
+				alpha_to_coverage_enabled: false,
+			},
+			multiview: None,
+		}));
+		let render_pipeline = create_pipeline("render_pipeline", wgpu::BlendState::REPLACE, true, settings.depth_mode.compare());
+		let ghost_pipeline = create_pipeline("ghost_pipeline", wgpu::BlendState::ALPHA_BLENDING, false, settings.depth_mode.compare_or_equal());
+
+		let storm_shader = device.create_shader_module(wgpu::include_wgsl!("storm.wgsl"));
+		let storm_bind_group_layout = &(device.create_bind_group_layout(&(wgpu::BindGroupLayoutDescriptor {
+			entries: &[
+				wgpu::BindGroupLayoutEntry {
+					binding: 0,
+					visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+					ty: wgpu::BindingType::Buffer {
+						ty: wgpu::BufferBindingType::Uniform,
+						has_dynamic_offset: false,
+						min_binding_size: None,
+					},
+					count: None,
+				}
+			],
+			label: Some("storm_bind_group_layout"),
+		})));
+		let storm_uniform = StormUniform::new(&(device));
+		let storm_bind_group = device.create_bind_group(&(wgpu::BindGroupDescriptor {
+			layout: storm_bind_group_layout,
+			entries: &[
+				wgpu::BindGroupEntry {
+					binding: 0,
+					resource: storm_uniform.as_entire_binding(),
+				}
+			],
+			label: Some("storm_bind_group"),
+		}));
+		let scene_copy = obj::Material::new(&(device), &(texture_bind_group_layout), Texture::screen(&(device), &(config), Some("scene_copy")));
+		let storm_wall = storm::wall_vertices();
+		let storm_buffer = device.create_buffer_init(&(wgpu::util::BufferInitDescriptor {
+			label: Some("storm_wall_buffer"),
+			contents: bytemuck::cast_slice(&(storm_wall)),
+			usage: wgpu::BufferUsages::VERTEX,
+		}));
+		let storm_pipeline_layout = device.create_pipeline_layout(&(wgpu::PipelineLayoutDescriptor {
+			label: Some("storm_pipeline_layout"),
+			bind_group_layouts: &[camera_bind_group_layout, storm_bind_group_layout, &(texture_bind_group_layout)],
+			push_constant_ranges: &[],
+		}));
+		// seen from both inside and out, and see-through (it bends a copy of the scene) so it doesn't write depth
+		let storm_pipeline = device.create_render_pipeline(&(wgpu::RenderPipelineDescriptor {
+			label: Some("storm_pipeline"),
+			layout: Some(&(storm_pipeline_layout)),
+			vertex: wgpu::VertexState {
+				module: &(storm_shader),
+				entry_point: "vs_main",
+				buffers: &[
+					wgpu::VertexBufferLayout {
+						array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
+						step_mode: wgpu::VertexStepMode::Vertex,
+						attributes: &(wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x2]),
+					},
+				],
+			},
+			fragment: Some(wgpu::FragmentState {
+				module: &(storm_shader),
+				entry_point: "fs_main",
+				targets: &[Some(wgpu::ColorTargetState {
+					format: config.format,
+					blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+					write_mask: wgpu::ColorWrites::ALL,
+				})],
+			}),
+			primitive: wgpu::PrimitiveState {
+				topology: wgpu::PrimitiveTopology::TriangleList,
+				strip_index_format: None,
+				front_face: wgpu::FrontFace::Ccw,
+				cull_mode: None,
+				polygon_mode: wgpu::PolygonMode::Fill,
+				unclipped_depth: false,
+				conservative: false,
+			},
+			depth_stencil: Some(wgpu::DepthStencilState {
+				format: wgpu::TextureFormat::Depth32Float,
+				depth_write_enabled: false,
+				depth_compare: settings.depth_mode.compare(),
 				stencil: wgpu::StencilState::default(),
 				bias: wgpu::DepthBiasState::default(),
 			}),
@@ -235,9 +582,8 @@ impl State {
 			},
 			multiview: None,
 		}));
-		return Ok(Self {
-			input,
 
+		return Ok(Self {
 			fullscreen: false,
 			focused: false,
 			window,
@@ -248,15 +594,53 @@ impl State {
 			queue,
 			config,
 			render_pipeline,
+			ghost_pipeline,
+			storm_pipeline,
 
-			player,
-			camera,
-			camera_uniform,
-			camera_bind_group,
+			players,
+			collision,
+			buildings: Buildings::new(),
+			props: Props::new(),
+			projectiles: Projectiles::new(),
+			pickups: Pickups::new(),
+			chests: Chests::new(),
+			loot,
+			storm,
+			bus,
+			match_controller: MatchController::new(settings.team_mode, settings.local_players + settings.bots),
+			seed,
+			friendly_fire: settings.friendly_fire,
+			damage_events: Vec::new(),
+			eliminations: Vec::new(),
+			local_players,
+			bots,
+			navmesh: NavMesh::empty(),
+			depth_mode: settings.depth_mode,
 
 			depth_view,
 
 			skin,
+
+			build_buffer,
+			build_materials,
+			prop_buffer,
+			prop_materials,
+			pickup_buffer,
+			pickup_materials,
+			chest_buffer,
+			chest_materials,
+			bus_buffer,
+			bus_materials,
+			player_buffer,
+			player_materials,
+			player_generation: 0,
+			ghost_materials,
+			storm_uniform,
+			storm_bind_group,
+			storm_buffer,
+			storm_vertices: storm_wall.len() as u32,
+			scene_copy,
+			texture_bind_group_layout,
 		});
 	}
 
@@ -335,44 +719,127 @@ impl State {
 		self.config.height = new_size.height;
 		self.surface.configure(&(self.device), &(self.config));
 		self.depth_view = depth_view(&(self.device), &(self.config));
-		self.camera.reconfigure(new_size);
+		self.scene_copy = obj::Material::new(&(self.device), &(self.texture_bind_group_layout), Texture::screen(&(self.device), &(self.config), Some("scene_copy")));
+		let count = self.local_players.len();
+		for (index, local_player) in self.local_players.iter_mut().enumerate() {
+			local_player.reconfigure(Viewport::split(new_size, count, index));
+		}
 
 		return;
 	}
 
 	pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
-		self.camera_uniform.set_view_projection_matrix(&(self.queue), &(self.camera));
-		
+		for local_player in &mut(self.local_players) {
+			local_player.write_camera(&(self.queue), local_player.view_camera());
+			local_player.write_ghost(&(self.queue));
+		}
+		self.write_world();
+
 		let output = self.surface.get_current_texture()?;
-		let view = output.texture.create_view(&(wgpu::TextureViewDescriptor::default()));
 		let mut encoder = self.device.create_command_encoder(&(wgpu::CommandEncoderDescriptor {
 			label: Some("encoder"),
 		}));
+		let views = self.local_players
+			.iter()
+			.map(|l| View { viewport: l.viewport, player: Some(l.player), camera_bind_group: l.camera_bind_group(), ghost: l.ghost() })
+			.collect::<Vec<_>>();
+		self.draw(&mut(encoder), &(output.texture), &(views));
+
+		// submit will accept anything that implements IntoIter
+		self.queue.submit(std::iter::once(encoder.finish()));
+		output.present();
+
+		return Ok(());
+	}
 
-		let mut render_pass = encoder.begin_render_pass(&(wgpu::RenderPassDescriptor {
-			label: Some("render_pass"),
+	fn write_world(&mut self) {
+		self.build_buffer.update(&(self.device), &(self.queue), self.buildings.generation(), || self.buildings.vertices());
+		self.prop_buffer.update(&(self.device), &(self.queue), self.props.generation(), || self.props.vertices());
+		self.pickup_buffer.update(&(self.device), &(self.queue), self.pickups.generation(), || self.pickups.vertices());
+		self.chest_buffer.update(&(self.device), &(self.queue), self.chests.generation(), || self.chests.vertices());
+		self.bus_buffer.update(&(self.device), &(self.queue), self.bus.generation(), || self.bus.vertices());
+		self.player_generation += 1;
+		self.player_buffer.update(&(self.device), &(self.queue), self.player_generation, || player::vertices(&(self.players)));
+		self.storm_uniform.write(&(self.queue), &(self.storm));
+		return;
+	}
+
+	fn begin_pass<'a>(&'a self, encoder: &'a mut wgpu::CommandEncoder, label: &str, view: &'a wgpu::TextureView, clear: bool) -> wgpu::RenderPass<'a> {
+		return encoder.begin_render_pass(&(wgpu::RenderPassDescriptor {
+			label: Some(label),
 			color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-				view: &(view),
+				view,
 				resolve_target: None,
 				ops: wgpu::Operations {
-					load: wgpu::LoadOp::Clear(Default::default()),
+					load: if clear { wgpu::LoadOp::Clear(Default::default()) } else { wgpu::LoadOp::Load },
 					store: true,
 				},
 			})],
 			depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
 				view: &(self.depth_view),
 				depth_ops: Some(wgpu::Operations {
-					load: wgpu::LoadOp::Clear(1.0),
+					load: if clear { wgpu::LoadOp::Clear(self.depth_mode.clear_value()) } else { wgpu::LoadOp::Load },
 					store: true,
 				}),
 				stencil_ops: None,
 			}),
 		}));
-		render_pass.set_pipeline(&(self.render_pipeline));
+	}
 
-		// camera
-		render_pass.set_bind_group(0, &(self.camera_bind_group), &[]);
+	// each view gets its own part of the target, with its own camera
+	fn draw(&self, encoder: &mut wgpu::CommandEncoder, target: &wgpu::Texture, views: &[View]) {
+		let target_view = target.create_view(&(wgpu::TextureViewDescriptor::default()));
+		let mut render_pass = self.begin_pass(encoder, "render_pass", &(target_view), true);
+		for view in views {
+			render_pass.set_pipeline(&(self.render_pipeline));
+			view.apply(&mut(render_pass));
+			self.draw_scene(&mut(render_pass));
+			self.draw_players(&mut(render_pass), view.player);
+		}
+		drop(render_pass);
+
+		// the storm bends whatever's behind it, so it needs a copy of that to sample
+		encoder.copy_texture_to_texture(target.as_image_copy(), self.scene_copy.diffuse_texture.texture.as_image_copy(), target.size());
+
+		let mut render_pass = self.begin_pass(encoder, "storm_pass", &(target_view), false);
+		for view in views {
+			view.apply(&mut(render_pass));
+
+			render_pass.set_pipeline(&(self.storm_pipeline));
+			render_pass.set_bind_group(1, &(self.storm_bind_group), &[]);
+			render_pass.set_bind_group(2, &(self.scene_copy.bind_group), &[]);
+			render_pass.set_vertex_buffer(0, self.storm_buffer.slice(..));
+			render_pass.draw(0..self.storm_vertices, 0..1);
 
+			// drawn last so what's behind it shows through
+			if let Some((buffer, vertices, valid)) = view.ghost {
+				render_pass.set_pipeline(&(self.ghost_pipeline));
+				let material = &(self.ghost_materials[if valid { 0 } else { 1 }]);
+				render_pass.set_bind_group(1, &(material.bind_group), &[]);
+				render_pass.set_vertex_buffer(0, buffer.slice(..));
+				render_pass.draw(0..vertices, 0..1);
+			}
+		}
+
+		drop(render_pass);
+
+		return;
+	}
+
+	fn draw_players<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, viewer: Option<usize>) {
+		render_pass.set_vertex_buffer(0, self.player_buffer.buffer.slice(..));
+		for (index, range) in self.player_buffer.ranges.iter().enumerate() {
+			if range.is_empty() || Some(index) == viewer {
+				continue;
+			}
+			let knocked = matches!(self.players[index].life, Life::Knocked { .. });
+			render_pass.set_bind_group(1, &(self.player_materials[knocked as usize].bind_group), &[]);
+			render_pass.draw(range.clone(), 0..1);
+		}
+		return;
+	}
+
+	fn draw_scene<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
 		// player
 		let skin = &(self.skin);
 		fn _rot_rect(w: f32, h: f32, r: Rad<f32>) -> [Point3<f32>; 6] {
@@ -419,32 +886,636 @@ impl State {
 			render_pass.draw_indexed(0..mesh.num_elements, 0, 0..1);
 		}
 
-		drop(render_pass);
+		self.build_buffer.draw(render_pass, &(self.build_materials));
+		self.prop_buffer.draw(render_pass, &(self.prop_materials));
+		self.pickup_buffer.draw(render_pass, &(self.pickup_materials));
+		self.chest_buffer.draw(render_pass, &(self.chest_materials));
+		self.bus_buffer.draw(render_pass, &(self.bus_materials));
 
-		// submit will accept anything that implements IntoIter
-		self.queue.submit(std::iter::once(encoder.finish()));
-		output.present();
+		return;
+	}
+
+	// renders a camera path at a fixed rate into numbered pngs, without presenting
+	pub fn render_camera_path<T: AsRef<std::path::Path>>(&mut self, path: CameraPath, fps: f32, out_dir: T) -> Result<(), &'static str> {
+		let out_dir = out_dir.as_ref();
+		std::fs::create_dir_all(out_dir).map_err(|_| "failed to create output directory")?;
+
+		let (width, height) = (self.config.width, self.config.height);
+		let texture = self.device.create_texture(&(wgpu::TextureDescriptor {
+			label: Some("capture_texture"),
+			size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+			mip_level_count: 1,
+			sample_count: 1,
+			dimension: wgpu::TextureDimension::D2,
+			format: self.config.format,
+			usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+			view_formats: &[],
+		}));
+
+		let padded_row = (width * 4).div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT) * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+		let buffer = self.device.create_buffer(&(wgpu::BufferDescriptor {
+			label: Some("capture_buffer"),
+			usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+			size: (padded_row * height) as u64,
+			mapped_at_creation: false,
+		}));
+		self.write_world();
+		let bgra = matches!(self.config.format, wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb);
+
+		// always rendered full size from the first local player's camera
+		let local_player = &(self.local_players[0]);
+		let viewport = Viewport::full(self.size);
+		let mut playback = CameraPlayback::new(path, &(local_player.camera));
+		playback.camera.reconfigure(viewport.size());
+		let frame_times = playback.frame_times(fps).collect::<Vec<f32>>();
+		for (frame, time) in frame_times.into_iter().enumerate() {
+			playback.seek(time);
+			local_player.write_camera(&(self.queue), &(playback.camera));
+
+			let mut encoder = self.device.create_command_encoder(&(wgpu::CommandEncoderDescriptor {
+				label: Some("capture_encoder"),
+			}));
+			let views = [View { viewport, player: None, camera_bind_group: local_player.camera_bind_group(), ghost: None }];
+			self.draw(&mut(encoder), &(texture), &(views));
+			encoder.copy_texture_to_buffer(
+				texture.as_image_copy(),
+				wgpu::ImageCopyBuffer {
+					buffer: &(buffer),
+					layout: wgpu::ImageDataLayout {
+						offset: 0,
+						bytes_per_row: Some(padded_row),
+						rows_per_image: Some(height),
+					},
+				},
+				wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+			);
+			self.queue.submit(std::iter::once(encoder.finish()));
+
+			let slice = buffer.slice(..);
+			slice.map_async(wgpu::MapMode::Read, |_| ());
+			self.device.poll(wgpu::Maintain::Wait);
+
+			let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+			for row in slice.get_mapped_range().chunks(padded_row as usize) {
+				pixels.extend_from_slice(&(row[..(width * 4) as usize]));
+			}
+			buffer.unmap();
+			if bgra {
+				for pixel in pixels.chunks_mut(4) {
+					pixel.swap(0, 2);
+				}
+			}
+
+			let image = image::RgbaImage::from_raw(width, height, pixels).ok_or("bad capture size")?;
+			image.save(out_dir.join(format!("{frame:05}.png"))).map_err(|_| "failed to save frame")?;
+		}
 
 		return Ok(());
 	}
 
 	pub fn update_camera(&mut self, dt: f32, sf: f32) {
-		self.player.update_pos(&(self.input), dt);
-		self.player.update_rot(&(self.input), sf);
+		self.damage_events.clear();
+		for local_player in &mut(self.local_players) {
+			let player = &mut(self.players[local_player.player]);
+			if let Some(speed) = local_player.update(player, &(self.collision), dt, sf) {
+				self.damage_events.extend(fall_damage_event(local_player.player, player, speed));
+			}
+		}
+		for (index, player) in self.players.iter().enumerate() {
+			// bleeding stops while someone's reviving them
+			let reviving = self.players.iter().any(|other| matches!(other.reviving, Some((revived, _)) if revived == index));
+			if matches!(player.life, Life::Knocked { .. }) && !reviving {
+				self.damage_events.push(DamageEvent {
+					attacker: None,
+					target: DamageTarget::Player(index),
+					kind: DamageKind::Bleed,
+					amount: BLEED_RATE * dt,
+					headshot: false,
+					point: player.position,
+				});
+			}
+		}
+		self.update_tools(dt);
+		return;
+	}
+
+	// whatever each local player has out: an edit, a build piece, a weapon or the pickaxe.
+	// aiming at a piece and pressing edit starts an edit, pressing it again applies it.
+	// build pieces are previewed, and placed while fire is held.
+	fn update_tools(&mut self, dt: f32) {
+		let mut effects = ToolEffects { destroyed: self.buildings.update(dt, &mut(self.collision)), ..Default::default() };
+
+		let mut local_players = std::mem::take(&mut(self.local_players));
+		for local_player in &mut(local_players) {
+			let eye = local_player.camera.position.unwrap();
+			let look = local_player.camera.look_dir();
+			let (ghost, kick) = self.use_tools(local_player.player, &mut(local_player.input), eye, look, dt, &mut(effects));
+			local_player.ghost = ghost;
+			if let Some(kick) = kick {
+				local_player.camera_effects.kick(&mut(local_player.camera), &mut(self.players[local_player.player]), kick);
+			}
+		}
+		self.local_players = local_players;
+		self.apply_tool_effects(effects, 0);
+		return;
+	}
+
+	// only ever stepped at the fixed timestep, so bots play the same at any frame rate.
+	// they think, move and use their tools all in one go, with whatever they put in `input`.
+	pub fn update_bots(&mut self, dt: f32) {
+		let from = self.damage_events.len();
+		let mut effects = ToolEffects::default();
+		let mut bots = std::mem::take(&mut(self.bots));
+		for bot in &mut(bots) {
+			bot.think(dt, &(self.bot_view()));
+			// bots turn by however much they want each update, so there is no mouse motion to spread out
+			let player = &mut(self.players[bot.player]);
+			if let Some(speed) = player.apply_input(&mut(bot.input), &(self.collision), dt, 1.0) {
+				self.damage_events.extend(fall_damage_event(bot.player, player, speed));
+			}
+			let (eye, look) = bot.look(&(self.players[bot.player]));
+			let (_, kick) = self.use_tools(bot.player, &mut(bot.input), eye, look, dt, &mut(effects));
+			if let Some(kick) = kick {
+				bot.kick(&mut(self.players[bot.player]), kick);
+			}
+		}
+		self.bots = bots;
+		self.apply_tool_effects(effects, from);
+		return;
+	}
 
-		self.camera.update_rot(&(self.input), sf);
-		self.camera.set_pos(self.player.adapt_pos_for_camera());
+	// once everyone's had their go, along with the damage they did from damage_events[from]
+	fn apply_tool_effects(&mut self, effects: ToolEffects, from: usize) {
+		for index in effects.revived {
+			self.players[index].revive();
+		}
+		for piece in effects.destroyed {
+			self.piece_destroyed(&(piece));
+		}
+		self.apply_damage(from);
 		return;
 	}
+
+	// one player's go with whatever they have out, from `eye` looking along `look`.
+	// returns the build preview to show and the recoil to apply to their view, if any.
+	fn use_tools(
+		&mut self,
+		index: usize,
+		input: &mut Input,
+		eye: Point3<f32>,
+		look: Vector3<f32>,
+		dt: f32,
+		effects: &mut ToolEffects,
+	) -> (Option<Ghost>, Option<Vector2<Deg<f32>>>) {
+		let teammate = if input.interacting {
+			player::nearest_knocked_teammate(&(self.players), index, REVIVE_REACH)
+		} else {
+			None
+		};
+		let player = &mut(self.players[index]);
+		player.build_cooldown = (player.build_cooldown - dt).max(0.0);
+		player.swing_cooldown = (player.swing_cooldown - dt).max(0.0);
+		let pick_up = std::mem::take(&mut(input.pick_up));
+		let drop_item = std::mem::take(&mut(input.drop_item));
+		// knocked players can only crawl, and nothing can be used on the way into the match
+		if player.life != Life::Alive || player.move_mode.is_dropping() {
+			player.opening = None;
+			player.reviving = None;
+			player.using = None;
+			return (None, None);
+		}
+
+		// holding interact next to a knocked teammate picks them back up after a while,
+		// and comes before any chest
+		player.reviving = match (teammate, player.reviving) {
+			(Some(other), Some((reviving, held))) if other == reviving => Some((other, held + dt)),
+			(Some(other), _) => Some((other, dt)),
+			(None, _) => None,
+		};
+		if let Some((other, held)) = player.reviving {
+			if held >= player::REVIVE_TIME {
+				player.reviving = None;
+				effects.revived.push(other);
+			}
+		}
+
+		// building, editing or reviving stops anything being used
+		if player.building.is_some() || player.editing.is_some() || player.reviving.is_some() {
+			player.using = None;
+		}
+		player.update_using(dt);
+
+		// holding interact next to a chest opens it after a moment
+		let chest = if input.interacting && teammate.is_none() { self.chests.nearest(player.position, PICKUP_REACH) } else { None };
+		player.opening = match (chest, player.opening) {
+			(Some(id), Some((opening, held))) if id == opening => Some((id, held + dt)),
+			(Some(id), _) => Some((id, dt)),
+			(None, _) => None,
+		};
+		if let Some((id, held)) = player.opening {
+			if held >= chest::OPEN_TIME && self.chests.open(id) {
+				player.opening = None;
+				// seeded by which chest it is, so it doesn't matter when it's opened
+				let items = self.loot.chest.roll(&mut(Rng::stream(self.seed, rng::LOOT_CHEST | id as u64)));
+				self.pickups.drop_items(self.chests.get(id).unwrap().position, items);
+			}
+		}
+
+		// whatever doesn't fit goes back where it was
+		if pick_up {
+			if let Some(index) = self.pickups.nearest(player.position, PICKUP_REACH) {
+				let pickup = self.pickups.take(index);
+				if let Some(rest) = player.inventory.pick_up(pickup.item) {
+					self.pickups.place(pickup.position, rest);
+				}
+			}
+		}
+		if drop_item {
+			if let Some(item) = player.inventory.drop_selected() {
+				self.pickups.place(player.position + player.forward_right().0 * DROP_DISTANCE, item);
+			}
+		}
+		let toggle_edit = std::mem::take(&mut(input.toggle_edit));
+		let reset_edit = std::mem::take(&mut(input.reset_edit));
+		let rotate_edit = std::mem::take(&mut(input.rotate_edit));
+		if toggle_edit {
+			match player.editing.take() {
+				Some(session) => {
+					if self.buildings.edit(session.piece, session.pattern, &mut(self.collision)).is_ok() {
+						self.navmesh.update_area(&(self.collision), self.buildings.get(session.piece).unwrap().placement.aabb());
+					}
+				}
+				None => {
+					player.editing = self.collision
+						.raycast(eye, look, EDIT_REACH)
+						.and_then(|hit| self.buildings.with_collider(hit.collider))
+						.map(|id| EditSession::new(id, self.buildings.get(id).unwrap().edit));
+					if player.editing.is_some() {
+						player.building = None;
+						player.inventory.put_away_selected();
+					}
+				}
+			};
+		}
+
+		if let Some(session) = &mut(player.editing) {
+			// the piece might have gone away mid-edit
+			let Some(piece) = self.buildings.get(session.piece) else {
+				player.editing = None;
+				return (None, None);
+			};
+			let placement = piece.placement;
+			if reset_edit {
+				if self.buildings.edit(session.piece, EditPattern::default(), &mut(self.collision)).is_ok() {
+					self.navmesh.update_area(&(self.collision), placement.aabb());
+				}
+				player.editing = None;
+				return (None, None);
+			}
+			if rotate_edit {
+				session.pattern = session.pattern.rotate(placement.kind);
+			}
+			let aimed = placement.aimed_tile(eye, look, EDIT_REACH);
+			session.update(placement.kind, aimed, input.firing);
+			return (Some((placement, session.pattern.inverse(placement.kind), false)), None);
+		}
+
+		if player.building.is_none() {
+			if player.inventory.selected.is_some() {
+				if input.firing {
+					player.start_using();
+				}
+				let movement = player.movement_state();
+				let mut shot = None;
+				let mut kick = None;
+				if let Some((weapon, reserve)) = player.inventory.equipped_weapon() {
+					shot = weapon.update(dt, input.firing, movement, input.aiming, reserve).map(|shot| (*weapon, shot));
+				}
+				if let Some((fired, shot)) = shot {
+					kick = Some(shot.kick);
+					let stats = fired.stats();
+					for dir in shot.pellet_dirs(index, look, stats.pellets) {
+						match stats.projectile {
+							Some(projectile) => self.projectiles.spawn(projectile, fired, index, eye, dir),
+							None => {
+								let event = weapon::fire(&(fired), index, eye, dir, &(self.players), &(self.collision), &(self.buildings));
+								self.damage_events.extend(event);
+							}
+						};
+					}
+					self.players[index].inventory.remove_spent();
+				}
+				return (None, kick);
+			}
+		}
+
+		let Some(kind) = player.building else {
+			// nothing else out, so swing the pickaxe
+			if input.firing && player.swing_cooldown == 0.0 {
+				player.swing_cooldown = SWING_INTERVAL;
+				if let Some(hit) = self.collision.raycast(eye, look, PICKAXE_REACH) {
+					if let Some(id) = self.buildings.with_collider(hit.collider) {
+						effects.destroyed.extend(self.buildings.damage(id, PICKAXE_DAMAGE, &mut(self.collision)));
+					} else if let Some(id) = self.props.with_collider(hit.collider) {
+						if let Some((material, amount)) = self.props.hit(id, PICKAXE_DAMAGE, &mut(self.collision)) {
+							player.inventory.resources.add(material, amount);
+						}
+					}
+				}
+			}
+			return (None, None);
+		};
+
+		let placement = Placement::from_aim(kind, player.position, eye, look);
+		let material = player.build_material;
+		let valid = self.buildings.can_place(&(placement)) && player.inventory.resources.get(material) >= PIECE_COST;
+		if valid && input.firing && player.build_cooldown == 0.0 {
+			player.inventory.resources.spend(material, PIECE_COST);
+			if self.buildings.place(placement, material, &mut(self.collision)).is_ok() {
+				self.navmesh.update_area(&(self.collision), placement.aabb());
+			}
+			player.build_cooldown = BUILD_INTERVAL;
+		}
+		return (Some((placement, EditPattern::default(), valid)), None);
+	}
+
+	// applies damage_events from `from` onwards
+	fn apply_damage(&mut self, from: usize) {
+		let mut destroyed = Vec::new();
+		for index in from..self.damage_events.len() {
+			let event = self.damage_events[index];
+			match event.target {
+				DamageTarget::Piece(id) => destroyed.extend(self.buildings.damage(id, event.amount, &mut(self.collision))),
+				DamageTarget::Player(victim) => {
+					let team = self.players[victim].team;
+					// teammates can't hurt each other (or themselves) without friendly fire
+					let friendly = event.attacker.is_some_and(|attacker| self.players[attacker].team == team);
+					if friendly && !self.friendly_fire {
+						continue;
+					}
+					let can_knock = self.players
+						.iter()
+						.enumerate()
+						.any(|(other, player)| other != victim && player.team == team && player.life == Life::Alive);
+					let player = &mut(self.players[victim]);
+					if player.take_damage(event.amount, event.kind, event.attacker, can_knock) == Some(Life::Eliminated) {
+						self.eliminate(victim, event.kind);
+					}
+				}
+			};
+		}
+		for piece in destroyed {
+			self.piece_destroyed(&(piece));
+		}
+		return;
+	}
+
+	// records the elimination and drops everything the player had.
+	// once nobody on their team is still up, knocked teammates are out too.
+	fn eliminate(&mut self, victim: usize, kind: DamageKind) {
+		let mut eliminated = vec![(victim, kind)];
+		let team = self.players[victim].team;
+		if !self.players.iter().any(|player| player.team == team && player.life == Life::Alive) {
+			for (index, player) in self.players.iter_mut().enumerate() {
+				if let Life::Knocked { cause, .. } = player.life {
+					if player.team == team {
+						player.eliminate();
+						eliminated.push((index, cause));
+					}
+				}
+			}
+		}
+
+		for (index, kind) in eliminated {
+			let player = &mut(self.players[index]);
+			self.eliminations.push(EliminationEvent { victim: index, killer: player.last_attacker, kind });
+			self.pickups.drop_items(player.position, player.inventory.take_all());
+		}
+		return;
+	}
+
+	// only ever stepped at the fixed timestep, so flight paths don't depend on frame rate
+	pub fn update_projectiles(&mut self, dt: f32) {
+		let (events, explosions) = self.projectiles.update(dt, &mut(self.players), &(self.collision), &(self.buildings));
+		let from = self.damage_events.len();
+		self.damage_events.extend(events);
+		self.apply_damage(from);
+
+		for center in explosions {
+			for local_player in &mut(self.local_players) {
+				let distance = (self.players[local_player.player].position - center).magnitude();
+				local_player.camera_effects.add_trauma(0.6 * (1.0 - distance / EXPLOSION_SHAKE_RANGE).max(0.0));
+			}
+		}
+		return;
+	}
+
+	// only ever stepped at the fixed timestep, so ticks land the same every time.
+	// it doesn't start closing until everyone's out of the bus.
+	pub fn update_storm(&mut self, dt: f32) {
+		if self.match_controller.phase != MatchPhase::InProgress {
+			return;
+		}
+		let Some(damage) = self.storm.update(dt) else {
+			return;
+		};
+		let circle = self.storm.circle();
+		let from = self.damage_events.len();
+		for (index, player) in self.players.iter().enumerate() {
+			if player.life != Life::Eliminated && !circle.contains(player.position) {
+				self.damage_events.push(DamageEvent {
+					attacker: None,
+					target: DamageTarget::Player(index),
+					kind: DamageKind::Storm,
+					amount: damage,
+					headshot: false,
+					point: player.position,
+				});
+			}
+		}
+		self.apply_damage(from);
+		return;
+	}
+
+	// only ever stepped at the fixed timestep, like projectiles.
+	// anyone still aboard at the end of its path gets dropped there.
+	pub fn update_bus(&mut self, dt: f32) {
+		if self.match_controller.phase != MatchPhase::Bus {
+			return;
+		}
+		self.bus.update(dt);
+		for player in &mut(self.players) {
+			if player.move_mode != MoveMode::InBus {
+				continue;
+			}
+			player.position = self.bus.position();
+			player.velocity = self.bus.velocity();
+			if self.bus.finished() {
+				player.leave_bus();
+			}
+		}
+		return;
+	}
+
+	// only ever stepped at the fixed timestep, like projectiles
+	pub fn update_match(&mut self, dt: f32) {
+		let events = self.match_controller.update(dt, &(self.players), self.bus.finished());
+		for &event in &(events) {
+			match event {
+				MatchEvent::PhaseChanged(MatchPhase::Warmup) => self.start_warmup(),
+				MatchEvent::PhaseChanged(MatchPhase::Bus) => self.start_bus(),
+				// each match's seed comes from the last one's, so any of them
+				// can be played again by starting with the seed it had
+				MatchEvent::PhaseChanged(MatchPhase::Ended) => self.seed = Rng::stream(self.seed, rng::MATCH).next_u64(),
+				_ => (),
+			};
+		}
+
+		// nothing counts during warmup, so anyone eliminated is straight back in
+		if self.match_controller.phase == MatchPhase::Warmup {
+			for index in 0..self.players.len() {
+				if self.players[index].life == Life::Eliminated {
+					self.respawn_on_island(index);
+				}
+			}
+		}
+		return;
+	}
+
+	fn respawn_on_island(&mut self, index: usize) {
+		let player = &mut(self.players[index]);
+		let position = WARMUP_ISLAND + Vector3::unit_x() * (1.5 * index as f32);
+		*player = Player::new(position, player.rot_x, player.team);
+		player.give_warmup_loadout();
+		return;
+	}
+
+	// also how the last match is cleared away: whatever was built, opened,
+	// dropped or fired goes, and the storm and bus go back to the start
+	fn start_warmup(&mut self) {
+		self.buildings.clear(&mut(self.collision));
+		self.chests.clear(&mut(self.collision));
+		self.props.clear(&mut(self.collision));
+		self.spawn_props();
+		self.pickups.clear();
+		self.projectiles.projectiles.clear();
+		self.eliminations.clear();
+		self.storm.restart(STORM_START, self.seed);
+		self.bus.restart(&(STORM_START), self.seed);
+		for bot in &mut(self.bots) {
+			bot.restart(self.seed);
+		}
+		for index in 0..self.players.len() {
+			self.respawn_on_island(index);
+		}
+		return;
+	}
+
+	// a fresh start for everyone: whatever was left on the island is cleared away,
+	// the map's loot is put out and everyone gets on the bus
+	fn start_bus(&mut self) {
+		self.pickups.clear();
+		self.eliminations.clear();
+		self.spawn_loot();
+		self.navmesh = NavMesh::build(&(self.collision), &(STORM_START), NAVMESH_CELL);
+		for player in &mut(self.players) {
+			*player = Player::new(self.bus.position(), player.rot_x, player.team);
+			player.board_bus();
+		}
+		return;
+	}
+
+	// something to harvest until there's an actual map
+	fn spawn_props(&mut self) {
+		for (kind, x, z) in [
+			(PropKind::Tree, 6.0, 4.0),
+			(PropKind::Tree, 9.0, -3.0),
+			(PropKind::Tree, -5.0, 8.0),
+			(PropKind::Tree, -8.0, -6.0),
+			(PropKind::Rock, 4.0, -8.0),
+			(PropKind::Rock, -10.0, 2.0),
+			(PropKind::Car, 12.0, 7.0),
+		] {
+			self.props.spawn(kind, Point3::new(x, 0.0, z), &mut(self.collision));
+		}
+		return;
+	}
+
+	fn spawn_loot(&mut self) {
+		for (index, (x, z)) in [(2.0, 6.0), (-3.0, -4.0), (7.0, -7.0), (-9.0, -1.0), (14.0, 2.0), (0.0, 12.0), (-12.0, 10.0), (8.0, 12.0)].into_iter().enumerate() {
+			let items = self.loot.floor.roll(&mut(Rng::stream(self.seed, rng::LOOT_FLOOR | index as u64)));
+			self.pickups.drop_items(Point3::new(x, 0.0, z), items);
+		}
+		for (x, z) in [(2.0, -4.0), (-7.0, 3.0), (16.0, -2.0)] {
+			self.chests.spawn(Point3::new(x, 0.0, z), &mut(self.collision));
+		}
+		return;
+	}
+
+	fn bot_view(&self) -> BotView<'_> {
+		return BotView {
+			players: &(self.players),
+			pickups: &(self.pickups),
+			chests: &(self.chests),
+			collision: &(self.collision),
+			navmesh: &(self.navmesh),
+			safe: self.storm.circle(),
+			next_safe: self.storm.to,
+			storm_active: self.match_controller.phase == MatchPhase::InProgress,
+			reach: PICKUP_REACH,
+		};
+	}
+
+	// effects for a piece breaking, whether it was destroyed or collapsed
+	fn piece_destroyed(&mut self, piece: &Piece) {
+		self.navmesh.update_area(&(self.collision), piece.placement.aabb());
+		let center = piece.placement.center();
+		for local_player in &mut(self.local_players) {
+			let player = &(self.players[local_player.player]);
+			let distance = (player.position - center).magnitude();
+			local_player.camera_effects.add_trauma(0.3 * (1.0 - distance / COLLAPSE_SHAKE_RANGE).max(0.0));
+		}
+		return;
+	}
+
+	// debug camera and camera paths are driven from the first local player's keyboard
+	pub fn toggle_camera_recording<T: AsRef<std::path::Path>>(&mut self, file_name: T) -> Result<(), &'static str> {
+		return self.local_players[0].toggle_camera_recording(file_name);
+	}
+	pub fn toggle_camera_playback<T: AsRef<std::path::Path>>(&mut self, file_name: T) -> Result<(), &'static str> {
+		return self.local_players[0].toggle_camera_playback(file_name);
+	}
+	pub fn scale_camera_playback_speed(&mut self, factor: f32) {
+		return self.local_players[0].scale_camera_playback_speed(factor);
+	}
+	pub fn toggle_debug_camera(&mut self) {
+		return self.local_players[0].toggle_debug_camera();
+	}
+	pub fn snap_debug_camera(&mut self) {
+		return self.local_players[0].snap_debug_camera();
+	}
+
 	pub fn process_key(&mut self, key: winit::keyboard::KeyCode, state: winit::event::ElementState) {
-		return self.input.process_key(key, state);
+		for local_player in &mut(self.local_players) {
+			let bindings = local_player.bindings.clone();
+			local_player.active_input().process_key(&(bindings), key, state);
+		}
+		return;
+	}
+	// the mouse always belongs to the first local player
+	pub fn process_mouse_button(&mut self, button: winit::event::MouseButton, state: winit::event::ElementState) {
+		return self.local_players[0].active_input().process_mouse_button(button, state);
+	}
+	pub fn process_mouse_wheel(&mut self, delta: winit::event::MouseScrollDelta) {
+		return self.local_players[0].active_input().process_scroll(delta);
 	}
 	pub fn add_mouse_motion(&mut self, delta: (f64, f64)) {
-		self.input.mouse_moved.0 += delta.0 as f32;
-		self.input.mouse_moved.1 += delta.1 as f32;
+		let input = self.local_players[0].active_input();
+		input.mouse_moved.0 += delta.0 as f32;
+		input.mouse_moved.1 += delta.1 as f32;
 		return;
 	}
 	pub fn set_mouse_motion(&mut self, delta: (f64, f64)) {
-		return self.input.set_mouse_motion(delta);
+		return self.local_players[0].active_input().set_mouse_motion(delta);
 	}
-}
\ No newline at end of file
+}
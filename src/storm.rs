@@ -0,0 +1,185 @@
+use std::path::Path;
+use cgmath::{Point2, Point3, Vector2, InnerSpace};
+
+use crate::{obj::Vertex, rng::{self, Rng}};
+
+// seconds between storm damage
+const TICK: f32 = 1.0;
+const WALL_HEIGHT: f32 = 200.0;
+const WALL_SEGMENTS: usize = 96;
+
+#[derive(Debug, Clone, Copy)]
+pub struct StormPhase {
+	// before the circle starts closing
+	pub wait: f32,
+	pub shrink_time: f32,
+	// to everyone outside, every tick until the next phase
+	pub damage: f32,
+	pub end_radius: f32,
+}
+
+impl StormPhase {
+	// one line per phase: wait shrink_time damage end_radius
+	pub fn load<T: AsRef<Path>>(file_name: T) -> Result<Vec<Self>, &'static str> {
+		let text = std::fs::read_to_string(file_name).map_err(|_| "failed to read storm phases")?;
+
+		let mut phases = Vec::new();
+		for line in text.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')) {
+			let values = line
+				.split_whitespace()
+				.map(|v| v.parse::<f32>())
+				.collect::<Result<Vec<f32>, _>>()
+				.map_err(|_| "invalid number in storm phases")?;
+			let [wait, shrink_time, damage, end_radius] = values[..] else {
+				return Err("storm phase needs 4 values");
+			};
+			phases.push(Self { wait, shrink_time, damage, end_radius });
+		}
+		if phases.windows(2).any(|w| w[1].end_radius > w[0].end_radius) {
+			return Err("storm phases have to get smaller");
+		}
+		return Ok(phases);
+	}
+}
+
+// on the ground, so x and z
+#[derive(Debug, Clone, Copy)]
+pub struct Circle {
+	pub center: Point2<f32>,
+	pub radius: f32,
+}
+
+impl Circle {
+	pub fn contains(&self, position: Point3<f32>) -> bool {
+		return (Point2::new(position.x, position.z) - self.center).magnitude() <= self.radius;
+	}
+
+	fn lerp(&self, to: &Circle, t: f32) -> Circle {
+		return Circle {
+			center: self.center + (to.center - self.center) * t,
+			radius: self.radius + (to.radius - self.radius) * t,
+		};
+	}
+
+	// somewhere the whole of a circle of `radius` fits inside this one
+	fn random_inside(&self, radius: f32, rng: &mut Rng) -> Circle {
+		let room = (self.radius - radius).max(0.0);
+		let distance = rng.next_f32().sqrt() * room;
+		let (sin, cos) = rng.range(0.0, std::f32::consts::TAU).sin_cos();
+		return Circle { center: self.center + Vector2::new(cos, sin) * distance, radius };
+	}
+}
+
+pub struct Storm {
+	phases: Vec<StormPhase>,
+	// index into phases, past the end once the last circle has closed
+	pub phase: usize,
+	// into the current phase
+	pub elapsed: f32,
+	// since the storm started, for animating the wall
+	pub time: f32,
+	// the circle the phase started with, and where it's closing to
+	pub from: Circle,
+	pub to: Circle,
+	rng: Rng,
+	since_tick: f32,
+}
+
+impl Storm {
+	pub fn new(phases: Vec<StormPhase>, start: Circle, seed: u64) -> Self {
+		let mut rng = Rng::stream(seed, rng::STORM);
+		let to = match phases.first() {
+			Some(phase) => start.random_inside(phase.end_radius, &mut(rng)),
+			None => start,
+		};
+		return Self { phases, phase: 0, elapsed: 0.0, time: 0.0, from: start, to, rng, since_tick: 0.0 };
+	}
+
+	// the same circles over again, for the next match
+	pub fn restart(&mut self, start: Circle, seed: u64) {
+		*self = Self::new(std::mem::take(&mut(self.phases)), start, seed);
+		return;
+	}
+
+	// the safe zone right now
+	pub fn circle(&self) -> Circle {
+		let Some(phase) = self.phases.get(self.phase) else {
+			return self.to;
+		};
+		let t = ((self.elapsed - phase.wait) / phase.shrink_time.max(f32::EPSILON)).clamp(0.0, 1.0);
+		return self.from.lerp(&(self.to), t);
+	}
+
+	pub fn damage(&self) -> f32 {
+		return self.phases.get(self.phase).or(self.phases.last()).map_or(0.0, |phase| phase.damage);
+	}
+
+	// how much to hurt everyone outside, when it's time to
+	pub fn update(&mut self, dt: f32) -> Option<f32> {
+		self.time += dt;
+		if let Some(phase) = self.phases.get(self.phase) {
+			self.elapsed += dt;
+			if self.elapsed >= phase.wait + phase.shrink_time {
+				self.phase += 1;
+				self.elapsed = 0.0;
+				self.from = self.to;
+				if let Some(next) = self.phases.get(self.phase) {
+					self.to = self.from.random_inside(next.end_radius, &mut(self.rng));
+				}
+			}
+		}
+
+		self.since_tick += dt;
+		if self.since_tick < TICK {
+			return None;
+		}
+		self.since_tick -= TICK;
+		return Some(self.damage());
+	}
+}
+
+// a cylinder of radius 1 around the origin, placed and sized by the shader.
+// tex_coords are how far round it is and the height.
+pub fn wall_vertices() -> Vec<Vertex> {
+	let mut vertices = Vec::new();
+	for segment in 0..WALL_SEGMENTS {
+		let corner = |i: usize, y: f32| {
+			let u = i as f32 / WALL_SEGMENTS as f32;
+			let (sin, cos) = (u * std::f32::consts::TAU).sin_cos();
+			return Vertex { position: [cos, y, sin], tex_coords: [u, y] };
+		};
+		let (a, b) = (corner(segment, 0.0), corner(segment + 1, 0.0));
+		let (c, d) = (corner(segment + 1, WALL_HEIGHT), corner(segment, WALL_HEIGHT));
+		vertices.extend_from_slice(&[a, b, c, a, c, d]);
+	}
+	return vertices;
+}
+
+pub struct StormUniform {
+	buffer: wgpu::Buffer,
+}
+
+impl StormUniform {
+	pub fn new(device: &wgpu::Device) -> Self {
+		return Self {
+			buffer: device.create_buffer(&(wgpu::BufferDescriptor {
+				label: Some("storm_buffer"),
+				usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+				size: std::mem::size_of::<[f32; 4]>() as u64,
+				mapped_at_creation: false,
+			})),
+		};
+	}
+
+	// centre x and z, radius, time
+	pub fn write(&self, queue: &wgpu::Queue, storm: &Storm) {
+		let circle = storm.circle();
+		let data = [circle.center.x, circle.center.y, circle.radius, storm.time];
+		queue.write_buffer(&(self.buffer), 0, bytemuck::cast_slice(&(data)));
+		return;
+	}
+
+	pub fn as_entire_binding(&self) -> wgpu::BindingResource<'_> {
+		return self.buffer.as_entire_binding();
+	}
+}
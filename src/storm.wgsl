@@ -0,0 +1,56 @@
+@group(0) @binding(0)
+var<uniform> camera: mat4x4<f32>;
+
+// centre x and z, radius, seconds since the storm started
+@group(1) @binding(0)
+var<uniform> storm: vec4<f32>;
+
+// everything drawn before the storm, the size of the whole target
+@group(2) @binding(0)
+var scene: texture_2d<f32>;
+@group(2) @binding(1)
+var scene_sampler: sampler;
+
+// vertex shader
+
+struct VertexInput {
+	@location(0) position: vec3<f32>,
+	@location(1) tex_coords: vec2<f32>,
+};
+
+struct VertexOutput {
+	@builtin(position) clip_position: vec4<f32>,
+	@location(0) tex_coords: vec2<f32>,
+};
+
+@vertex
+fn vs_main(
+	model: VertexInput,
+) -> VertexOutput {
+	let time = storm.w;
+	// the wall ripples in and out a little so it doesn't look solid
+	let wobble = 1.0 + 0.01 * sin(model.tex_coords.x * 60.0 + time * 2.0) * sin(model.position.y * 0.2 + time * 1.3);
+	let radius = storm.z * wobble;
+	let position = vec3<f32>(storm.x + model.position.x * radius, model.position.y, storm.y + model.position.z * radius);
+
+	var out: VertexOutput;
+	out.clip_position = camera * vec4<f32>(position, 1.0);
+	out.tex_coords = model.tex_coords;
+	return out;
+}
+
+// fragment shader
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+	let time = storm.w;
+	// waves bent by other waves, for a heat haze sort of look
+	let uv = vec2<f32>(in.tex_coords.x * 64.0, in.tex_coords.y);
+	let warp = sin(uv.y * 0.35 + time * 1.7 + sin(uv.x * 0.9 + time));
+	let bands = sin(uv.x * 1.3 + warp * 2.0 + time * 0.8) * 0.5 + 0.5;
+	let colour = mix(vec3<f32>(0.35, 0.1, 0.55), vec3<f32>(0.6, 0.3, 0.9), bands);
+	// what's behind the wall, pushed about by the same waves, in pixels
+	let shift = vec2<f32>(warp, sin(uv.x * 0.7 - time * 1.1 + warp)) * 6.0;
+	let behind = textureSample(scene, scene_sampler, (in.clip_position.xy + shift) / vec2<f32>(textureDimensions(scene)));
+	return vec4<f32>(mix(behind.rgb, colour, 0.25 + 0.2 * bands), 1.0);
+}
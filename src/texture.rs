@@ -111,4 +111,39 @@ impl Texture {
 		
 		return Self { texture, view, sampler };
 	}
+
+	// the size and format of what's being drawn to, for copying it into and sampling
+	pub fn screen(
+		device: &wgpu::Device,
+		config: &wgpu::SurfaceConfiguration,
+		label: Option<&str>
+	) -> Self {
+		let texture = device.create_texture(&(wgpu::TextureDescriptor {
+			label,
+			size: wgpu::Extent3d {
+				width: config.width,
+				height: config.height,
+				depth_or_array_layers: 1,
+			},
+			mip_level_count: 1,
+			sample_count: 1,
+			dimension: wgpu::TextureDimension::D2,
+			format: config.format,
+			usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+			view_formats: &[],
+		}));
+
+		let view = texture.create_view(&(wgpu::TextureViewDescriptor::default()));
+		let sampler = device.create_sampler(&(wgpu::SamplerDescriptor {
+			address_mode_u: wgpu::AddressMode::ClampToEdge,
+			address_mode_v: wgpu::AddressMode::ClampToEdge,
+			address_mode_w: wgpu::AddressMode::ClampToEdge,
+			mag_filter: wgpu::FilterMode::Linear,
+			min_filter: wgpu::FilterMode::Linear,
+			mipmap_filter: wgpu::FilterMode::Nearest,
+			..Default::default()
+		}));
+
+		return Self { texture, view, sampler };
+	}
 }
@@ -0,0 +1,498 @@
+use cgmath::{Point3, Vector2, Vector3, Deg, Rad, InnerSpace};
+
+use crate::{
+	build::{Buildings, PieceId},
+	collision::{CollisionWorld, ColliderId, ray_sphere},
+	player::{Player, Life, MovementState, HEAD_RADIUS},
+	rng::Rng,
+	projectile::ProjectileKind,
+	loot::Rarity,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeaponKind {
+	AssaultRifle,
+	Smg,
+	Shotgun,
+	Pistol,
+	Sniper,
+	RocketLauncher,
+	Grenade,
+}
+
+pub struct WeaponStats {
+	// per pellet, before falloff and headshots
+	pub damage: f32,
+	pub pellets: u32,
+	// shots per second
+	pub fire_rate: f32,
+	// keeps firing while the trigger is held
+	pub automatic: bool,
+	pub magazine: u32,
+	pub reload_time: f32,
+	// full damage up to falloff_start, dropping linearly to min_damage_scale at falloff_end
+	pub falloff_start: f32,
+	pub falloff_end: f32,
+	pub min_damage_scale: f32,
+	pub headshot_multiplier: f32,
+	// nothing past this is hit at all
+	pub range: f32,
+	// fired as a projectile instead of a hitscan trace
+	pub projectile: Option<ProjectileKind>,
+}
+
+impl WeaponKind {
+	pub fn stats(self) -> WeaponStats {
+		return match self {
+			Self::AssaultRifle => WeaponStats {
+				damage: 30.0, pellets: 1, fire_rate: 5.5, automatic: true, magazine: 30, reload_time: 2.2,
+				falloff_start: 40.0, falloff_end: 90.0, min_damage_scale: 0.6, headshot_multiplier: 1.5, range: 250.0, projectile: None,
+			},
+			Self::Smg => WeaponStats {
+				damage: 17.0, pellets: 1, fire_rate: 12.0, automatic: true, magazine: 30, reload_time: 2.0,
+				falloff_start: 20.0, falloff_end: 50.0, min_damage_scale: 0.5, headshot_multiplier: 1.75, range: 150.0, projectile: None,
+			},
+			Self::Shotgun => WeaponStats {
+				damage: 9.0, pellets: 10, fire_rate: 1.0, automatic: false, magazine: 5, reload_time: 4.5,
+				falloff_start: 6.0, falloff_end: 20.0, min_damage_scale: 0.3, headshot_multiplier: 2.0, range: 40.0, projectile: None,
+			},
+			Self::Pistol => WeaponStats {
+				damage: 24.0, pellets: 1, fire_rate: 6.75, automatic: false, magazine: 16, reload_time: 1.5,
+				falloff_start: 25.0, falloff_end: 60.0, min_damage_scale: 0.5, headshot_multiplier: 2.0, range: 150.0, projectile: None,
+			},
+			Self::Sniper => WeaponStats {
+				damage: 105.0, pellets: 1, fire_rate: 0.33, automatic: false, magazine: 1, reload_time: 2.8,
+				falloff_start: 300.0, falloff_end: 500.0, min_damage_scale: 0.8, headshot_multiplier: 2.5, range: 500.0, projectile: Some(ProjectileKind::Bullet),
+			},
+			// damage comes from the explosion, not the hit
+			Self::RocketLauncher => WeaponStats {
+				damage: 0.0, pellets: 1, fire_rate: 1.0, automatic: false, magazine: 1, reload_time: 2.5,
+				falloff_start: 0.0, falloff_end: 1.0, min_damage_scale: 1.0, headshot_multiplier: 1.0, range: 0.0, projectile: Some(ProjectileKind::Rocket),
+			},
+			// thrown one at a time, the magazine is the most that can be carried
+			Self::Grenade => WeaponStats {
+				damage: 0.0, pellets: 1, fire_rate: 1.0, automatic: false, magazine: 6, reload_time: 0.0,
+				falloff_start: 0.0, falloff_end: 1.0, min_damage_scale: 1.0, headshot_multiplier: 1.0, range: 0.0, projectile: Some(ProjectileKind::Grenade),
+			},
+		};
+	}
+}
+
+// how accurate a weapon is, all angles in degrees
+pub struct Accuracy {
+	// cone half-angle standing still from the hip
+	pub base_spread: f32,
+	// multipliers on top of base_spread
+	pub moving: f32,
+	pub airborne: f32,
+	pub crouched: f32,
+	pub ads: f32,
+	// the first shot after a pause goes exactly where aimed when not moving
+	pub first_shot_accurate: bool,
+	// extra spread from sustained fire, decaying per second once it stops
+	pub bloom_per_shot: f32,
+	pub max_bloom: f32,
+	pub bloom_decay: f32,
+	// average kick per shot (yaw, pitch), and how far each shot can stray from it
+	pub recoil: Vector2<f32>,
+	pub recoil_jitter: f32,
+}
+
+impl WeaponKind {
+	pub fn accuracy(self) -> Accuracy {
+		let accuracy = |base_spread, (moving, airborne, crouched, ads), first_shot_accurate, (bloom_per_shot, max_bloom, bloom_decay), (yaw, pitch), recoil_jitter| Accuracy {
+			base_spread, moving, airborne, crouched, ads,
+			first_shot_accurate,
+			bloom_per_shot, max_bloom, bloom_decay,
+			recoil: Vector2::new(yaw, pitch), recoil_jitter,
+		};
+		return match self {
+			Self::AssaultRifle => accuracy(1.2, (1.6, 3.0, 0.75, 0.5), true, (0.35, 3.0, 6.0), (0.1, 0.6), 0.25),
+			Self::Smg => accuracy(1.8, (1.4, 2.5, 0.8, 0.6), false, (0.2, 2.5, 8.0), (0.1, 0.35), 0.3),
+			Self::Shotgun => accuracy(5.0, (1.2, 1.6, 0.9, 0.8), false, (0.0, 0.0, 0.0), (0.0, 3.0), 0.5),
+			Self::Pistol => accuracy(1.0, (1.6, 3.0, 0.75, 0.5), true, (0.6, 3.0, 5.0), (0.0, 1.2), 0.3),
+			// hopeless from the hip, perfect scoped
+			Self::Sniper => accuracy(4.0, (1.5, 3.0, 0.8, 0.0), false, (0.0, 0.0, 0.0), (0.0, 4.0), 0.5),
+			Self::RocketLauncher => accuracy(0.5, (1.0, 1.0, 1.0, 1.0), false, (0.0, 0.0, 0.0), (0.0, 3.0), 0.5),
+			Self::Grenade => accuracy(0.0, (1.0, 1.0, 1.0, 1.0), true, (0.0, 0.0, 0.0), (0.0, 0.0), 0.0),
+		};
+	}
+}
+
+impl WeaponStats {
+	pub fn hit_damage(&self, distance: f32, headshot: bool) -> f32 {
+		let t = ((distance - self.falloff_start) / (self.falloff_end - self.falloff_start)).clamp(0.0, 1.0);
+		let damage = self.damage * (1.0 + (self.min_damage_scale - 1.0) * t);
+		return if headshot { damage * self.headshot_multiplier } else { damage };
+	}
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Weapon {
+	pub kind: WeaponKind,
+	pub rarity: Rarity,
+	pub ammo: u32,
+	// until the next shot can go off
+	cooldown: f32,
+	// time left, while reloading
+	pub reloading: Option<f32>,
+	// semi-automatic weapons need the trigger let go between shots
+	trigger_was_held: bool,
+
+	// extra spread, see Accuracy
+	pub bloom: f32,
+	// shots since the last pause, picks the step of the recoil pattern
+	burst: u32,
+	since_shot: f32,
+	// every shot ever fired, for seeding its spread
+	shots_fired: u32,
+}
+
+// a pause this long between shots starts a new burst
+const BURST_RESET: f32 = 0.4;
+
+// what a shot needs to know to go off the same way everywhere
+#[derive(Debug, Clone, Copy)]
+pub struct Shot {
+	// mix in who fired it before seeding an Rng with it
+	pub seed: u64,
+	pub spread: Deg<f32>,
+	// yaw, pitch
+	pub kick: Vector2<Deg<f32>>,
+}
+
+impl Shot {
+	pub fn rng(&self, owner: usize) -> Rng {
+		return Rng::new(((owner as u64) << 32) ^ self.seed);
+	}
+
+	// where each pellet goes, every one spread on its own
+	pub fn pellet_dirs(&self, owner: usize, look: Vector3<f32>, pellets: u32) -> Vec<Vector3<f32>> {
+		let mut rng = self.rng(owner);
+		return (0..pellets).map(|_| spread_dir(look, self.spread, &mut(rng))).collect();
+	}
+}
+
+// a random direction within `spread` of `dir`, uniform over the cone's base
+pub fn spread_dir(dir: Vector3<f32>, spread: Deg<f32>, rng: &mut Rng) -> Vector3<f32> {
+	let dir = dir.normalize();
+	if spread.0 <= 0.0 {
+		return dir;
+	}
+	let up = if dir.y.abs() < 0.99 { Vector3::unit_y() } else { Vector3::unit_x() };
+	let right = dir.cross(up).normalize();
+	let up = right.cross(dir);
+	let radius = rng.next_f32().sqrt() * Rad::from(spread).0.tan();
+	let (sin, cos) = (rng.next_f32() * std::f32::consts::TAU).sin_cos();
+	return (dir + right * (radius * cos) + up * (radius * sin)).normalize();
+}
+
+impl Weapon {
+	pub fn new(kind: WeaponKind, rarity: Rarity) -> Self {
+		return Self {
+			kind,
+			rarity,
+			ammo: kind.stats().magazine,
+			cooldown: 0.0,
+			reloading: None,
+			trigger_was_held: false,
+
+			bloom: 0.0,
+			burst: 0,
+			since_shot: BURST_RESET,
+			shots_fired: 0,
+		};
+	}
+
+	// rarer weapons hit harder
+	pub fn stats(&self) -> WeaponStats {
+		let mut stats = self.kind.stats();
+		stats.damage *= self.rarity.damage_scale();
+		return stats;
+	}
+
+	pub fn spread(&self, movement: MovementState, aiming: bool) -> Deg<f32> {
+		let accuracy = self.kind.accuracy();
+		let still = matches!(movement, MovementState::Idle | MovementState::CrouchIdle);
+		if accuracy.first_shot_accurate && self.burst == 0 && still {
+			return Deg(0.0);
+		}
+		let mut spread = accuracy.base_spread + self.bloom;
+		spread *= match movement {
+			MovementState::Idle | MovementState::Noclip => 1.0,
+			MovementState::Walking | MovementState::Sprinting | MovementState::Sliding => accuracy.moving,
+			MovementState::CrouchIdle | MovementState::CrouchWalking => accuracy.crouched,
+			MovementState::Airborne | MovementState::InBus | MovementState::Skydiving | MovementState::Gliding => accuracy.airborne,
+		};
+		if aiming {
+			spread *= accuracy.ads;
+		}
+		return Deg(spread);
+	}
+
+	// the same for every burst, so it can be learned
+	fn recoil(&self) -> Vector2<Deg<f32>> {
+		let accuracy = self.kind.accuracy();
+		let mut rng = Rng::new(((self.kind as u64) << 32) | self.burst as u64);
+		let jitter = accuracy.recoil_jitter;
+		return Vector2::new(
+			Deg(accuracy.recoil.x * rng.range(-1.0, 1.0) + jitter * rng.range(-0.5, 0.5)),
+			Deg(accuracy.recoil.y + jitter * rng.range(-0.5, 0.5)),
+		);
+	}
+
+	// only if there's spare ammo to reload with
+	pub fn reload(&mut self, reserve: u32) {
+		if self.reloading.is_none() && self.ammo < self.kind.stats().magazine && reserve > 0 {
+			self.reloading = Some(self.kind.stats().reload_time);
+		}
+		return;
+	}
+
+	// the shot, if one went off this update. an empty magazine reloads by itself.
+	// `reserve` is the ammo carried for it, None for things that are their own ammo.
+	pub fn update(&mut self, dt: f32, trigger: bool, movement: MovementState, aiming: bool, mut reserve: Option<&mut u32>) -> Option<Shot> {
+		let stats = self.kind.stats();
+		let accuracy = self.kind.accuracy();
+		let pulled = trigger && (stats.automatic || !self.trigger_was_held);
+		self.trigger_was_held = trigger;
+		self.cooldown = (self.cooldown - dt).max(0.0);
+		self.bloom = (self.bloom - accuracy.bloom_decay * dt).max(0.0);
+		self.since_shot += dt;
+		if self.since_shot >= BURST_RESET {
+			self.burst = 0;
+		}
+
+		if let Some(left) = &mut(self.reloading) {
+			*left -= dt;
+			if *left > 0.0 {
+				return None;
+			}
+			self.reloading = None;
+			if let Some(reserve) = reserve.as_deref_mut() {
+				let taken = (stats.magazine - self.ammo).min(*reserve);
+				*reserve -= taken;
+				self.ammo += taken;
+			}
+		}
+		if !pulled || self.cooldown > 0.0 {
+			return None;
+		}
+		if self.ammo == 0 {
+			self.reload(reserve.map_or(0, |reserve| *reserve));
+			return None;
+		}
+		self.ammo -= 1;
+		self.cooldown = 1.0 / stats.fire_rate;
+
+		let shot = Shot {
+			seed: ((self.kind as u64) << 48) | self.shots_fired as u64,
+			spread: self.spread(movement, aiming),
+			kick: self.recoil(),
+		};
+		self.shots_fired = self.shots_fired.wrapping_add(1);
+		self.burst += 1;
+		self.since_shot = 0.0;
+		self.bloom = (self.bloom + accuracy.bloom_per_shot).min(accuracy.max_bloom);
+		return Some(shot);
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DamageTarget {
+	// index into State::players
+	Player(usize),
+	Piece(PieceId),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DamageKind {
+	Fall,
+	Storm,
+	Weapon(WeaponKind),
+	// from whatever weapon fired the rocket or grenade
+	Explosion(WeaponKind),
+	// knocked players slowly losing health
+	Bleed,
+}
+
+impl DamageKind {
+	// the storm and falling go straight to health
+	pub fn hits_shield(self) -> bool {
+		return !matches!(self, Self::Fall | Self::Storm);
+	}
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DamageEvent {
+	// None for the world, e.g. falling or the storm
+	pub attacker: Option<usize>,
+	pub target: DamageTarget,
+	pub kind: DamageKind,
+	pub amount: f32,
+	pub headshot: bool,
+	pub point: Point3<f32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TraceTarget {
+	// index into the players, and whether it was the head
+	Player(usize, bool),
+	Collider(ColliderId),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TraceHit {
+	pub distance: f32,
+	pub point: Point3<f32>,
+	pub normal: Vector3<f32>,
+	pub target: TraceTarget,
+}
+
+// the first player hitbox or world surface along a normalized ray
+pub fn trace(
+	origin: Point3<f32>,
+	dir: Vector3<f32>,
+	max_distance: f32,
+	ignore: Option<usize>,
+	players: &[Player],
+	collision: &CollisionWorld,
+) -> Option<TraceHit> {
+	let mut hit = collision.raycast(origin, dir, max_distance).map(|hit| TraceHit {
+		distance: hit.distance,
+		point: hit.point,
+		normal: hit.normal,
+		target: TraceTarget::Collider(hit.collider),
+	});
+	let mut nearest = hit.map_or(max_distance, |hit| hit.distance);
+
+	for (index, player) in players.iter().enumerate() {
+		if Some(index) == ignore || player.life == Life::Eliminated {
+			continue;
+		}
+		let (body, head) = player.hitboxes();
+		let head_hit = ray_sphere(origin, dir, head, HEAD_RADIUS).map(|t| (t, true));
+		let body_hit = body.raycast(origin, dir).map(|t| (t, false));
+		for (t, headshot) in head_hit.into_iter().chain(body_hit) {
+			if t < nearest {
+				nearest = t;
+				hit = Some(TraceHit { distance: t, point: origin + dir * t, normal: -dir, target: TraceTarget::Player(index, headshot) });
+			}
+		}
+	}
+	return hit;
+}
+
+impl DamageTarget {
+	// what a trace hit, if it's something that takes damage
+	pub fn of(target: TraceTarget, buildings: &Buildings) -> Option<(Self, bool)> {
+		return match target {
+			TraceTarget::Player(index, headshot) => Some((Self::Player(index), headshot)),
+			TraceTarget::Collider(collider) => buildings.with_collider(collider).map(|piece| (Self::Piece(piece), false)),
+		};
+	}
+}
+
+// traces one shot (or pellet) from `origin` along `dir`, stopping at the first thing hit.
+// anything that isn't a player or a piece just stops the shot.
+pub fn fire(
+	weapon: &Weapon,
+	attacker: usize,
+	origin: Point3<f32>,
+	dir: Vector3<f32>,
+	players: &[Player],
+	collision: &CollisionWorld,
+	buildings: &Buildings,
+) -> Option<DamageEvent> {
+	let stats = weapon.stats();
+	let dir = dir.normalize();
+	let hit = trace(origin, dir, stats.range, Some(attacker), players, collision)?;
+	let (target, headshot) = DamageTarget::of(hit.target, buildings)?;
+	return Some(DamageEvent {
+		attacker: Some(attacker),
+		target,
+		kind: DamageKind::Weapon(weapon.kind),
+		amount: stats.hit_damage(hit.distance, headshot),
+		headshot,
+		point: hit.point,
+	});
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use cgmath::Deg;
+	use crate::{collision::cuboid, player::HEIGHT};
+
+	// standing at the origin, facing whoever's shooting from -x
+	fn target() -> Vec<Player> {
+		return vec![Player::new(Point3::new(0.0, 0.0, 0.0), Deg(180.0), 0)];
+	}
+
+	fn shoot(height: f32, collision: &CollisionWorld) -> Option<TraceHit> {
+		return trace(Point3::new(-10.0, height, 0.0), Vector3::unit_x(), 100.0, None, &(target()), collision);
+	}
+
+	#[test]
+	fn head_and_body_hitboxes() {
+		let collision = CollisionWorld::new(Vec::new());
+		let head = HEIGHT - HEAD_RADIUS;
+		assert_eq!(shoot(head, &(collision)).unwrap().target, TraceTarget::Player(0, true));
+		assert_eq!(shoot(0.5, &(collision)).unwrap().target, TraceTarget::Player(0, false));
+		assert!(shoot(HEIGHT + 0.1, &(collision)).is_none());
+		return;
+	}
+
+	#[test]
+	fn walls_block_shots() {
+		let collision = CollisionWorld::new(cuboid(Point3::new(-5.0, 0.0, 0.0), Vector3::new(0.5, 3.0, 3.0)));
+		let hit = shoot(0.5, &(collision)).unwrap();
+		assert_eq!(hit.target, TraceTarget::Collider(ColliderId::Static));
+		assert!(hit.distance < 5.0);
+
+		// the map isn't something that takes damage
+		let weapon = Weapon::new(WeaponKind::AssaultRifle, Rarity::Common);
+		let event = fire(&(weapon), 1, Point3::new(-10.0, 0.5, 0.0), Vector3::unit_x(), &(target()), &(collision), &(Buildings::new()));
+		assert!(event.is_none());
+		return;
+	}
+
+	#[test]
+	fn falloff_is_clamped() {
+		let stats = WeaponKind::AssaultRifle.stats();
+		assert_eq!(stats.hit_damage(0.0, false), stats.damage);
+		assert_eq!(stats.hit_damage(stats.falloff_start, false), stats.damage);
+		let middle = stats.hit_damage((stats.falloff_start + stats.falloff_end) / 2.0, false);
+		assert!((middle - stats.damage * (1.0 + stats.min_damage_scale) / 2.0).abs() < 1e-4);
+		assert_eq!(stats.hit_damage(stats.falloff_end, false), stats.damage * stats.min_damage_scale);
+		assert_eq!(stats.hit_damage(stats.range * 10.0, false), stats.damage * stats.min_damage_scale);
+		assert_eq!(stats.hit_damage(0.0, true), stats.damage * stats.headshot_multiplier);
+		return;
+	}
+
+	#[test]
+	fn snipers_need_scoping_in() {
+		let sniper = Weapon::new(WeaponKind::Sniper, Rarity::Common);
+		assert!(sniper.spread(MovementState::Idle, false).0 > 0.0);
+		assert_eq!(sniper.spread(MovementState::Idle, true).0, 0.0);
+		// unlike a rifle's first shot
+		let rifle = Weapon::new(WeaponKind::AssaultRifle, Rarity::Common);
+		assert_eq!(rifle.spread(MovementState::Idle, false).0, 0.0);
+		return;
+	}
+
+	#[test]
+	fn pellets_spread_apart() {
+		let shot = Shot { seed: 7, spread: Deg(5.0), kick: Vector2::new(Deg(0.0), Deg(0.0)) };
+		let look = Vector3::unit_x();
+		let dirs = shot.pellet_dirs(0, look, WeaponKind::Shotgun.stats().pellets);
+		assert_eq!(dirs.len(), 10);
+		for (i, a) in dirs.iter().enumerate() {
+			assert!(Rad(a.dot(look).min(1.0).acos()) <= Rad::from(Deg(5.0 + 1e-3)));
+			assert!(dirs[i + 1..].iter().all(|b| a != b));
+		}
+		// and the same shot goes the same way everywhere
+		assert_eq!(dirs, shot.pellet_dirs(0, look, 10));
+		return;
+	}
+}